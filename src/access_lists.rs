@@ -0,0 +1,38 @@
+use crate::ERC20;
+use near_sdk::AccountId;
+
+impl ERC20 {
+    pub fn set_allowlisted(&mut self, account_id: AccountId, listed: bool) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        if listed {
+            self.allowlist.insert(account_id);
+        } else {
+            self.allowlist.remove(&account_id);
+        }
+    }
+
+    pub fn set_denylisted(&mut self, account_id: AccountId, listed: bool) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        if listed {
+            self.denylist.insert(account_id);
+        } else {
+            self.denylist.remove(&account_id);
+        }
+    }
+
+    /// While set, only allowlisted accounts can receive transfers.
+    pub fn set_whitelist_mode(&mut self, whitelist_mode: bool) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.whitelist_mode = whitelist_mode;
+    }
+
+    /// The denylist overrides the allowlist; the allowlist only matters in
+    /// `whitelist_mode`.
+    pub fn can_receive(&self, account_id: &AccountId) -> bool {
+        !self.denylist.contains(account_id)
+            && (!self.whitelist_mode || self.allowlist.contains(account_id))
+    }
+}
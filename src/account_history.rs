@@ -0,0 +1,69 @@
+use crate::ERC20;
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::U128,
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+pub const ACCOUNT_HISTORY_CAPACITY: usize = 20;
+
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq,
+)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum OpType {
+    Mint,
+    Burn,
+    Send,
+    Receive,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountOperation {
+    pub op_type: OpType,
+    pub counterparty: Option<AccountId>,
+    pub amount: U128,
+    pub timestamp: u64,
+}
+
+impl ERC20 {
+    /// Keeps the last `ACCOUNT_HISTORY_CAPACITY` operations of each account.
+    pub(crate) fn record_account_operation(
+        &mut self,
+        account_id: &AccountId,
+        op_type: OpType,
+        counterparty: Option<&AccountId>,
+        amount: u128,
+    ) {
+        let operation = AccountOperation {
+            op_type,
+            counterparty: counterparty.cloned(),
+            amount: amount.into(),
+            timestamp: env::block_timestamp(),
+        };
+        match self.account_history.get_mut(account_id) {
+            Some(history) => {
+                if history.len() == ACCOUNT_HISTORY_CAPACITY {
+                    history.remove(0);
+                }
+                history.push(operation);
+            }
+            None => {
+                self.account_history
+                    .insert(account_id.clone(), vec![operation]);
+            }
+        }
+    }
+
+    /// The latest `limit` operations of `account_id`, oldest first.
+    pub fn account_history(&self, account_id: AccountId, limit: u64) -> Vec<AccountOperation> {
+        let Some(history) = self.account_history.get(&account_id) else {
+            return Vec::new();
+        };
+        let skip = history.len().saturating_sub(limit as usize);
+        history[skip..].to_vec()
+    }
+}
@@ -0,0 +1,58 @@
+use crate::ERC20;
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::U128,
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+pub const ALLOWANCE_HISTORY_CAPACITY: usize = 20;
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AllowanceChange {
+    pub value: U128,
+    pub timestamp: u64,
+}
+
+impl ERC20 {
+    /// Keeps the last `ALLOWANCE_HISTORY_CAPACITY` values of each allowance.
+    pub(crate) fn record_allowance_change(
+        &mut self,
+        owner: &AccountId,
+        spender: &AccountId,
+        value: u128,
+    ) {
+        let change = AllowanceChange {
+            value: value.into(),
+            timestamp: env::block_timestamp(),
+        };
+        let key = (owner.clone(), spender.clone());
+        match self.allowance_history.get_mut(&key) {
+            Some(history) => {
+                if history.len() == ALLOWANCE_HISTORY_CAPACITY {
+                    history.remove(0);
+                }
+                history.push(change);
+            }
+            None => {
+                self.allowance_history.insert(key, vec![change]);
+            }
+        }
+    }
+
+    /// The latest `limit` changes of the allowance, oldest first.
+    pub fn allowance_history(
+        &self,
+        owner: AccountId,
+        spender: AccountId,
+        limit: u64,
+    ) -> Vec<AllowanceChange> {
+        let Some(history) = self.allowance_history.get(&(owner, spender)) else {
+            return Vec::new();
+        };
+        let skip = history.len().saturating_sub(limit as usize);
+        history[skip..].to_vec()
+    }
+}
@@ -0,0 +1,162 @@
+use crate::ERC20;
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::U128,
+    serde::{Deserialize, Serialize},
+    store::UnorderedMap,
+    AccountId, Promise,
+};
+
+/// Balance store. The `lookup-balances` feature swaps in a `LookupMap`,
+/// which skips the key index `UnorderedMap` keeps for iteration, making
+/// every new holder cheaper at the cost of the views that enumerate holders.
+#[cfg(not(feature = "lookup-balances"))]
+pub type BalanceMap = UnorderedMap<AccountId, BalanceEntry>;
+#[cfg(feature = "lookup-balances")]
+pub type BalanceMap = near_sdk::store::LookupMap<AccountId, BalanceEntry>;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct BalanceEntry {
+    pub amount: u128,
+    pub locked: u128,
+    pub last_transfer_ts: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountInfo {
+    pub balance: U128,
+    pub locked: U128,
+    pub last_transfer_ts: Option<u64>,
+    pub transfer_count: u64,
+}
+
+impl ERC20 {
+    pub fn get_account_info(&self, account_id: AccountId) -> Option<AccountInfo> {
+        self.balance.get(&account_id).map(|entry| AccountInfo {
+            balance: entry.amount.into(),
+            locked: entry.locked.into(),
+            last_transfer_ts: entry.last_transfer_ts,
+            transfer_count: self.transfer_count_of(account_id.clone()),
+        })
+    }
+
+    /// Outbound transfers made through `internal_transfer`. Kept apart from
+    /// the balance entry so the count survives the account being emptied.
+    pub fn transfer_count_of(&self, account_id: AccountId) -> u64 {
+        self.transfer_counts
+            .get(&account_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Accounts holding a non-zero balance.
+    pub fn holder_count(&self) -> u64 {
+        self.holder_count
+    }
+
+    /// Every balance write goes through here so `holder_count` follows
+    /// accounts moving between zero and non-zero and the wrapped ledger
+    /// never exceeds the balance.
+    pub(crate) fn internal_set_balance(&mut self, account_id: &AccountId, amount: u128) {
+        let previous = match self.balance.get_mut(account_id) {
+            Some(entry) => std::mem::replace(&mut entry.amount, amount),
+            None => {
+                self.balance.insert(
+                    account_id.clone(),
+                    BalanceEntry {
+                        amount,
+                        ..Default::default()
+                    },
+                );
+                self.internal_index_account(account_id);
+                0
+            }
+        };
+        if previous == 0 && amount > 0 {
+            self.holder_count += 1;
+        } else if previous > 0 && amount == 0 {
+            self.holder_count -= 1;
+        }
+        if amount < previous {
+            self.internal_clamp_wrapped(account_id, amount);
+        }
+    }
+
+    /// Appends `account_id` to `account_index` the first time it gets a
+    /// balance entry, allowance or reservation. Accounts are never dropped
+    /// from the index, so a position stays valid as a cursor across calls.
+    #[cfg(not(feature = "lookup-balances"))]
+    pub(crate) fn internal_index_account(&mut self, account_id: &AccountId) {
+        if self.indexed_accounts.insert(account_id.clone()) {
+            self.account_index.push(account_id.clone());
+        }
+    }
+
+    #[cfg(feature = "lookup-balances")]
+    pub(crate) fn internal_index_account(&mut self, _account_id: &AccountId) {}
+
+    pub(crate) fn internal_credit(&mut self, account_id: &AccountId, amount: u128) {
+        let balance = self.balance.get(account_id).map_or(0, |entry| entry.amount);
+        let balance = balance.checked_add(amount).expect("balance overflow");
+        self.internal_set_balance(account_id, balance);
+    }
+
+    /// Drops the entry of `account_id`, refunds its storage deposit and
+    /// returns the amount it held.
+    pub(crate) fn internal_remove_balance(&mut self, account_id: &AccountId) -> u128 {
+        let amount = self.balance.get(account_id).map_or(0, |entry| entry.amount);
+        self.internal_set_balance(account_id, 0);
+        self.balance.remove(account_id);
+        if let Some(deposit) = self.storage_deposits.remove(account_id) {
+            self.total_storage_deposits -= deposit;
+            if deposit > 0 {
+                Promise::new(account_id.clone()).transfer(deposit);
+            }
+        }
+        amount
+    }
+
+    /// Converts state written before `BalanceEntry`, when balances, locks
+    /// and transfer timestamps lived in separate maps. Meant for the
+    /// embedding contract's migration, which passes the maps read from its
+    /// old state; every holder is visited in one call, so very large holder
+    /// sets need a staged migration instead.
+    pub fn migrate_balance_entries(
+        &mut self,
+        mut legacy_balance: UnorderedMap<AccountId, u128>,
+        mut legacy_locked: UnorderedMap<AccountId, u128>,
+        mut legacy_last_transfer_ts: UnorderedMap<AccountId, u64>,
+    ) {
+        for (account_id, amount) in legacy_balance.iter() {
+            if *amount > 0 {
+                self.holder_count += 1;
+            }
+            self.balance.insert(
+                account_id.clone(),
+                BalanceEntry {
+                    amount: *amount,
+                    locked: legacy_locked.get(account_id).copied().unwrap_or_default(),
+                    last_transfer_ts: legacy_last_transfer_ts.get(account_id).copied(),
+                },
+            );
+            self.internal_index_account(account_id);
+        }
+        for (account_id, locked) in legacy_locked.iter() {
+            if !self.balance.contains_key(account_id) {
+                self.balance.insert(
+                    account_id.clone(),
+                    BalanceEntry {
+                        locked: *locked,
+                        ..Default::default()
+                    },
+                );
+                self.internal_index_account(account_id);
+            }
+        }
+
+        legacy_balance.clear();
+        legacy_locked.clear();
+        legacy_last_transfer_ts.clear();
+    }
+}
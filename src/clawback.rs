@@ -0,0 +1,78 @@
+use crate::{events, ERC20, NANOS_PER_SEC};
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::U128,
+    require,
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MintRecord {
+    pub account_id: AccountId,
+    pub amount: U128,
+    pub minted_at: u64,
+}
+
+impl ERC20 {
+    /// While non-zero, every mint is recorded and can be reversed with
+    /// `clawback` for `secs` afterwards; zero stops recording new mints.
+    pub fn set_clawback_window(&mut self, secs: u64) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.clawback_window_secs = secs;
+    }
+
+    pub(crate) fn record_clawback_mint(&mut self, account_id: &AccountId, amount: u128) {
+        if self.clawback_window_secs == 0 {
+            return;
+        }
+        let mint_id = self.next_mint_record_id;
+        self.next_mint_record_id += 1;
+        self.mint_records.insert(
+            mint_id,
+            MintRecord {
+                account_id: account_id.clone(),
+                amount: amount.into(),
+                minted_at: env::block_timestamp(),
+            },
+        );
+        events::MintRecorded {
+            mint_id,
+            owner_id: account_id,
+            amount: amount.into(),
+        }
+        .emit(self);
+    }
+
+    /// Reverses mint `mint_id` of `account_id` while its window is open and
+    /// the account still holds the minted amount unlocked.
+    pub fn clawback(&mut self, account_id: AccountId, mint_id: u64) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        let record = self.mint_records.get(&mint_id).expect("no mint record");
+        require!(
+            record.account_id == account_id,
+            "mint belongs to another account"
+        );
+        require!(
+            env::block_timestamp() < record.minted_at + self.clawback_window_secs * NANOS_PER_SEC,
+            "clawback window expired"
+        );
+        let amount = record.amount.0;
+        let balance = *self.balance_of(account_id.clone()).unwrap_or(&0u128);
+        require!(
+            balance >= amount && balance - amount >= self.locked_of(account_id.clone()).0,
+            "minted tokens already spent"
+        );
+
+        self.mint_records.remove(&mint_id);
+        self.internal_burn(&account_id, amount);
+    }
+
+    pub fn mint_record(&self, mint_id: u64) -> Option<&MintRecord> {
+        self.mint_records.get(&mint_id)
+    }
+}
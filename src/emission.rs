@@ -0,0 +1,48 @@
+use crate::{ERC20, NANOS_PER_SEC};
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::U128,
+    AccountId,
+};
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Emission {
+    pub rate_per_second: u128,
+    pub recipient: AccountId,
+    pub last_accrual: u64,
+}
+
+impl ERC20 {
+    pub fn configure_emission(&mut self, rate_per_second: U128, recipient: AccountId) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.emission = Some(Emission {
+            rate_per_second: rate_per_second.0,
+            recipient,
+            last_accrual: env::block_timestamp(),
+        });
+    }
+
+    /// Mints whatever has been emitted since the last accrual, capped by
+    /// `max_supply`. Anyone may call it.
+    pub fn accrue_emission(&mut self) -> U128 {
+        self.assert_not_migrating();
+        let Some(emission) = self.emission.as_mut() else {
+            return 0.into();
+        };
+        let elapsed_secs = (env::block_timestamp() - emission.last_accrual) / NANOS_PER_SEC;
+        emission.last_accrual += elapsed_secs * NANOS_PER_SEC;
+        let mut amount = emission
+            .rate_per_second
+            .saturating_mul(elapsed_secs as u128);
+        let recipient = emission.recipient.clone();
+        if let Some(max_supply) = self.max_supply {
+            amount = amount.min(max_supply.saturating_sub(self.total_supply));
+        }
+        if amount > 0 {
+            self.internal_mint(recipient, amount.into());
+        }
+        amount.into()
+    }
+}
@@ -0,0 +1,188 @@
+use crate::ERC20;
+use near_sdk::{json_types::U128, log, serde::Serialize, serde_json::json, AccountId};
+
+/// NEP-297 event logs.
+pub const EVENTS_STANDARD: &str = "nep297";
+pub const DEFAULT_EVENT_STANDARD: &str = "nep141";
+pub const DEFAULT_EVENT_VERSION: &str = "1.0.0";
+
+fn emit_event<T: Serialize>(token: &ERC20, event: &str, data: &[T]) {
+    let envelope = json!({
+        "standard": token.event_standard,
+        "version": token.event_version,
+        "event": event,
+        "data": data,
+    });
+    log!("EVENT_JSON:{}", envelope);
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MetadataUpdate<'a> {
+    pub name: &'a str,
+    pub symbol: &'a str,
+}
+
+impl MetadataUpdate<'_> {
+    pub fn emit(self, token: &ERC20) {
+        emit_event(token, "metadata_update", &[self]);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AllowancesRevoked<'a> {
+    pub owner_id: &'a AccountId,
+    pub spender_ids: &'a [AccountId],
+}
+
+impl AllowancesRevoked<'_> {
+    pub fn emit(self, token: &ERC20) {
+        emit_event(token, "allowances_revoked", &[self]);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OperatorUpdate<'a> {
+    pub owner_id: &'a AccountId,
+    pub operator_id: &'a AccountId,
+    pub approved: bool,
+}
+
+impl OperatorUpdate<'_> {
+    pub fn emit(self, token: &ERC20) {
+        emit_event(token, "operator_update", &[self]);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OperatorTransfer<'a> {
+    pub operator_id: &'a AccountId,
+    pub owner_id: &'a AccountId,
+    pub receiver_id: &'a AccountId,
+    pub amount: U128,
+}
+
+impl OperatorTransfer<'_> {
+    pub fn emit(self, token: &ERC20) {
+        emit_event(token, "operator_transfer", &[self]);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Approval<'a> {
+    pub owner: &'a AccountId,
+    pub spender: &'a AccountId,
+    pub old_allowance: U128,
+    pub new_allowance: U128,
+}
+
+impl Approval<'_> {
+    pub fn emit(self, token: &ERC20) {
+        emit_event(token, "approval", &[self]);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtBurn<'a> {
+    pub owner_id: &'a AccountId,
+    pub amount: U128,
+}
+
+impl FtBurn<'_> {
+    pub fn emit(self, token: &ERC20) {
+        emit_event(token, "ft_burn", &[self]);
+    }
+
+    /// Logs all burns as the `data` of a single event.
+    pub fn emit_many(events: &[FtBurn<'_>], token: &ERC20) {
+        emit_event(token, "ft_burn", events);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BurnSkipped<'a> {
+    pub owner_id: &'a AccountId,
+    pub amount: U128,
+}
+
+impl BurnSkipped<'_> {
+    pub fn emit(self, token: &ERC20) {
+        emit_event(token, "burn_skipped", &[self]);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtTransfer<'a> {
+    pub old_owner_id: &'a AccountId,
+    pub new_owner_id: &'a AccountId,
+    pub amount: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<&'a str>,
+}
+
+impl FtTransfer<'_> {
+    pub fn emit(self, token: &ERC20) {
+        emit_event(token, "ft_transfer", &[self]);
+    }
+
+    /// Logs all transfers as the `data` of a single event.
+    pub fn emit_many(events: &[FtTransfer<'_>], token: &ERC20) {
+        emit_event(token, "ft_transfer", events);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtMint<'a> {
+    pub owner_id: &'a AccountId,
+    pub amount: U128,
+}
+
+impl FtMint<'_> {
+    pub fn emit(self, token: &ERC20) {
+        emit_event(token, "ft_mint", &[self]);
+    }
+
+    /// Logs all mints as the `data` of a single event.
+    pub fn emit_many(events: &[FtMint<'_>], token: &ERC20) {
+        emit_event(token, "ft_mint", events);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtResolveTransfer<'a> {
+    pub sender_id: &'a AccountId,
+    pub receiver_id: &'a AccountId,
+    pub used_amount: U128,
+    pub refunded_amount: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<&'a str>,
+}
+
+impl FtResolveTransfer<'_> {
+    pub fn emit(self, token: &ERC20) {
+        emit_event(token, "ft_resolve_transfer", &[self]);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MintRecorded<'a> {
+    pub mint_id: u64,
+    pub owner_id: &'a AccountId,
+    pub amount: U128,
+}
+
+impl MintRecorded<'_> {
+    pub fn emit(self, token: &ERC20) {
+        emit_event(token, "mint_recorded", &[self]);
+    }
+}
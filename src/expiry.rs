@@ -0,0 +1,55 @@
+use crate::ERC20;
+use near_sdk::{
+    env::{self, predecessor_account_id},
+    json_types::U128,
+    AccountId,
+};
+
+impl ERC20 {
+    /// Like `approve`, but the allowance reads as zero from `expires_at`
+    /// (nanoseconds) on.
+    pub fn approve_until(&mut self, spender: AccountId, value: U128, expires_at: u64) {
+        self.assert_not_migrating();
+        let owner = predecessor_account_id();
+        self.internal_approve(&owner, spender.clone(), value.into());
+        self.allowance_expiry.insert((owner, spender), expires_at);
+    }
+
+    /// True when `transfer_from` by `spender` cannot draw on an allowance
+    /// of `owner`: none was ever granted, or it reached its expiry.
+    pub fn is_allowance_expired(&self, owner: AccountId, spender: AccountId) -> bool {
+        let exists = self
+            .allowed
+            .get(&owner)
+            .is_some_and(|allowed| allowed.contains_key(&spender));
+        !exists || self.allowance_expiry_passed(&owner, &spender)
+    }
+
+    pub(crate) fn allowance_expiry_passed(&self, owner: &AccountId, spender: &AccountId) -> bool {
+        self.allowance_expiry
+            .get(&(owner.clone(), spender.clone()))
+            .is_some_and(|expires_at| *expires_at <= env::block_timestamp())
+    }
+
+    /// Removes expired allowances of `owner` to free their storage. Anyone
+    /// may call it; unexpired or missing entries are skipped. Returns how
+    /// many entries were removed.
+    pub fn sweep_expired_allowances(&mut self, owner: AccountId, spenders: Vec<AccountId>) -> u32 {
+        self.assert_not_migrating();
+        let mut swept = 0;
+        for spender in spenders {
+            if !self.allowance_expiry_passed(&owner, &spender) {
+                continue;
+            }
+            if let Some(allowed) = self.allowed.get_mut(&owner) {
+                allowed.remove(&spender);
+                if allowed.is_empty() {
+                    self.allowed.remove(&owner);
+                }
+            }
+            self.allowance_expiry.remove(&(owner.clone(), spender));
+            swept += 1;
+        }
+        swept
+    }
+}
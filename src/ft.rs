@@ -0,0 +1,277 @@
+use crate::{events, ERC20};
+use near_sdk::{
+    env::{self, predecessor_account_id},
+    ext_contract,
+    json_types::{Base64VecU8, U128},
+    log, require,
+    serde::{Deserialize, Serialize},
+    serde_json, AccountId, Gas, PromiseOrValue, PromiseResult,
+};
+
+/// NEP-141 core: `ft_transfer`, `ft_transfer_call`, `ft_total_supply` and
+/// `ft_balance_of`.
+pub const FT_CORE_STANDARD: &str = "nep141";
+pub const FT_METADATA_STANDARD: &str = "nep148";
+pub const FT_METADATA_SPEC: &str = "ft-1.0.0";
+
+/// NEP-148 metadata.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FungibleTokenMetadata {
+    pub spec: String,
+    pub name: String,
+    pub symbol: String,
+    pub icon: Option<String>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<Base64VecU8>,
+    pub decimals: u8,
+}
+
+const GAS_FOR_FT_ON_TRANSFER: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(5_000_000_000_000);
+
+#[ext_contract(ext_ft_receiver)]
+pub trait FungibleTokenReceiver {
+    /// Returns the amount of the transfer the receiver does not accept.
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128>;
+}
+
+#[ext_contract(ext_ft_resolver)]
+pub trait FungibleTokenResolver {
+    fn ft_resolve_transfer(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        correlation_id: Option<String>,
+    ) -> U128;
+}
+
+/// Protocol-owned account ids that no holder can control.
+const SYSTEM_ACCOUNT_IDS: &[&str] = &["system"];
+
+impl ERC20 {
+    pub fn ft_total_supply(&self) -> U128 {
+        self.total_supply.into()
+    }
+
+    pub fn ft_balance_of(&self, account_id: AccountId) -> U128 {
+        (*self.balance_of(account_id).unwrap_or(&0)).into()
+    }
+
+    pub fn ft_metadata(&self) -> FungibleTokenMetadata {
+        FungibleTokenMetadata {
+            spec: FT_METADATA_SPEC.to_string(),
+            name: self.name().to_string(),
+            symbol: self.symbol().to_string(),
+            icon: None,
+            reference: self.reference.clone(),
+            reference_hash: self.reference_hash.clone().map(Base64VecU8),
+            decimals: self.decimals_value(),
+        }
+    }
+
+    /// `reference_hash` is the sha256 of the JSON served at `reference`.
+    pub fn set_reference(
+        &mut self,
+        reference: Option<String>,
+        reference_hash: Option<Base64VecU8>,
+    ) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.reference = reference;
+        self.reference_hash = reference_hash.map(|hash| hash.0);
+    }
+
+    /// Whether `content` hashes to `reference_hash`; false while unset.
+    pub fn verify_reference(&self, content: Vec<u8>) -> bool {
+        self.reference_hash
+            .as_ref()
+            .is_some_and(|hash| *hash == env::sha256(&content))
+    }
+
+    /// NEP-141 transfer, optionally crediting a `referrer`. Must be exposed
+    /// as `#[payable]`.
+    pub fn ft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        referrer: Option<AccountId>,
+    ) {
+        self.assert_not_migrating();
+        require!(
+            env::attached_deposit() == 1,
+            "requires attached deposit of exactly 1 yoctoNEAR"
+        );
+        self.internal_ft_transfer(receiver_id, amount, memo, None);
+        if let Some(referrer) = referrer {
+            self.internal_credit_referrer(&predecessor_account_id(), &referrer);
+        }
+    }
+
+    /// NEP-141 transfer that calls `ft_on_transfer` on the receiver and
+    /// refunds whatever it returns unused. `correlation_id` is echoed in the
+    /// `ft_transfer` and `ft_resolve_transfer` events for tracing. Must be
+    /// exposed as `#[payable]`.
+    pub fn ft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+        correlation_id: Option<String>,
+    ) -> PromiseOrValue<U128> {
+        self.assert_not_migrating();
+        require!(
+            env::attached_deposit() == 1,
+            "requires attached deposit of exactly 1 yoctoNEAR"
+        );
+        require!(
+            !self.receiver_allowlist_enabled || self.allowed_receivers.contains(&receiver_id),
+            "receiver contract not allowlisted"
+        );
+        let sender_id = predecessor_account_id();
+        let delivered =
+            self.internal_ft_transfer(receiver_id.clone(), amount, memo, correlation_id.as_deref());
+        ext_ft_receiver::ext(receiver_id.clone())
+            .with_static_gas(GAS_FOR_FT_ON_TRANSFER)
+            .ft_on_transfer(sender_id.clone(), delivered.into(), msg)
+            .then(
+                ext_ft_resolver::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                    .ft_resolve_transfer(sender_id, receiver_id, delivered.into(), correlation_id),
+            )
+            .into()
+    }
+
+    /// While enabled, `ft_transfer_call` only calls receivers on the
+    /// `allowed_receivers` list.
+    pub fn set_receiver_allowlist_enabled(&mut self, enabled: bool) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.receiver_allowlist_enabled = enabled;
+    }
+
+    pub fn set_allowed_receiver(&mut self, receiver_id: AccountId, allowed: bool) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        if allowed {
+            self.allowed_receivers.insert(receiver_id);
+        } else {
+            self.allowed_receivers.remove(&receiver_id);
+        }
+    }
+
+    /// Moves the part of an `ft_transfer_call` the receiver returned unused
+    /// back to the sender, limited to what the receiver still holds, and
+    /// returns the amount used. Must be exposed as `#[private]`.
+    pub fn ft_resolve_transfer(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        correlation_id: Option<String>,
+    ) -> U128 {
+        let unused = match env::promise_result(0) {
+            PromiseResult::Successful(value) => serde_json::from_slice::<U128>(&value)
+                .map(|unused| unused.0.min(amount.0))
+                .unwrap_or(amount.0),
+            _ => amount.0,
+        };
+        let receiver_balance = *self.balance_of(receiver_id.clone()).unwrap_or(&0u128);
+        let refund = unused.min(receiver_balance);
+        if refund > 0 {
+            self.internal_set_balance(&receiver_id, receiver_balance - refund);
+            self.internal_credit(&sender_id, refund);
+        }
+        let used = amount.0 - refund;
+        events::FtResolveTransfer {
+            sender_id: &sender_id,
+            receiver_id: &receiver_id,
+            used_amount: used.into(),
+            refunded_amount: refund.into(),
+            correlation_id: correlation_id.as_deref(),
+        }
+        .emit(self);
+        used.into()
+    }
+
+    /// `ft_transfer` without the yocto deposit, for contracts on the
+    /// `no_deposit_callers` allowlist that cannot attach one.
+    pub fn transfer_no_deposit(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        self.assert_not_migrating();
+        require!(
+            self.no_deposit_callers.contains(&predecessor_account_id()),
+            "caller not allowlisted"
+        );
+        self.internal_ft_transfer(receiver_id, amount, memo, None);
+    }
+
+    pub fn set_no_deposit_caller(&mut self, account_id: AccountId, allowed: bool) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        if allowed {
+            self.no_deposit_callers.insert(account_id);
+        } else {
+            self.no_deposit_callers.remove(&account_id);
+        }
+    }
+
+    /// Returns the amount delivered after the burn fee.
+    fn internal_ft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        correlation_id: Option<&str>,
+    ) -> u128 {
+        require!(
+            !SYSTEM_ACCOUNT_IDS.contains(&receiver_id.as_str()),
+            "cannot transfer to system account"
+        );
+        let sender_id = predecessor_account_id();
+        self.internal_check_cooldown(&sender_id);
+        let delivered = self.internal_transfer_with_correlation(
+            &sender_id,
+            &receiver_id,
+            amount.0,
+            correlation_id,
+        );
+        if let Some(memo) = memo {
+            log!("Memo: {}", memo);
+        }
+        if self.refund_storage_on_empty {
+            self.internal_unregister_if_empty(&sender_id);
+        }
+        delivered
+    }
+
+    /// When set, an `ft_transfer` that empties the sender unregisters them
+    /// and refunds their storage deposit.
+    pub fn set_refund_storage_on_empty(&mut self, enabled: bool) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.refund_storage_on_empty = enabled;
+    }
+
+    fn internal_unregister_if_empty(&mut self, account_id: &AccountId) {
+        let is_empty = self
+            .balance
+            .get(account_id)
+            .is_none_or(|entry| entry.amount == 0 && entry.locked == 0);
+        if is_empty {
+            self.internal_remove_balance(account_id);
+        }
+    }
+}
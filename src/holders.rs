@@ -0,0 +1,45 @@
+use crate::{uint_types::U256, BPS_DENOMINATOR, ERC20};
+use near_sdk::{json_types::U128, AccountId};
+
+pub const MAX_TOP_HOLDERS: u64 = 50;
+
+impl ERC20 {
+    /// Every holder, highest balance first. This scans and sorts the whole
+    /// holder set, so gas grows with the holder count.
+    fn sorted_holders(&self) -> Vec<(&AccountId, u128)> {
+        let mut holders: Vec<(&AccountId, u128)> = self
+            .balance
+            .iter()
+            .map(|(account_id, entry)| (account_id, entry.amount))
+            .collect();
+        holders.sort_unstable_by_key(|(_, balance)| std::cmp::Reverse(*balance));
+        holders
+    }
+
+    /// Highest balances first, at most `MAX_TOP_HOLDERS`. Built on a scan of
+    /// every holder, so the view is only practical while that set stays
+    /// modest.
+    pub fn top_holders(&self, limit: u64) -> Vec<(AccountId, U128)> {
+        self.sorted_holders()
+            .into_iter()
+            .take(limit.min(MAX_TOP_HOLDERS) as usize)
+            .map(|(account_id, balance)| (account_id.clone(), U128(balance)))
+            .collect()
+    }
+
+    /// Share of the total supply held by the `top_n` largest holders, in
+    /// basis points.
+    pub fn concentration(&self, top_n: u64) -> u32 {
+        if self.total_supply == 0 {
+            return 0;
+        }
+        let held = self
+            .sorted_holders()
+            .into_iter()
+            .take(top_n as usize)
+            .fold(U256::zero(), |held, (_, balance)| {
+                held + U256::from(balance)
+            });
+        (held * U256::from(BPS_DENOMINATOR) / U256::from(self.total_supply)).as_u32()
+    }
+}
@@ -1,43 +1,459 @@
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
-    env::predecessor_account_id,
+    env::{self, predecessor_account_id},
+    ext_contract,
     json_types::U128,
-    log, require,
-    store::UnorderedMap,
-    AccountId, IntoStorageKey,
+    require,
+    serde::{Deserialize, Serialize},
+    serde_json,
+    store::{UnorderedMap, UnorderedSet, Vector},
+    AccountId, Balance, BorshStorageKey, Gas, IntoStorageKey, Promise, PromiseOrValue,
+    PromiseResult,
 };
+use std::collections::HashMap;
+use uint_types::U256;
+
+pub mod access_lists;
+pub mod account_history;
+pub mod allowance_history;
+pub mod balance;
+pub mod clawback;
+pub mod emission;
+pub mod events;
+pub mod expiry;
+pub mod ft;
+#[cfg(not(feature = "lookup-balances"))]
+pub mod holders;
+pub mod mint_confirmation;
+pub mod multisig;
+pub mod observer;
+pub mod periodic;
+pub mod permit;
+pub mod recovery;
+pub mod redenomination;
+pub mod referral;
+pub mod roles;
+pub mod signature;
+pub mod storage;
+pub mod transfer_log;
+pub mod wrap;
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    // Balance, Locked and LastTransfer are retired by `BalanceEntries` but
+    // keep their place so later keys do not shift.
+    #[allow(dead_code)]
+    Balance,
+    Allowed,
+    Reservations,
+    LastPull,
+    UpgradeHistory,
+    Frozen,
+    #[allow(dead_code)]
+    Locked,
+    Operators,
+    #[allow(dead_code)]
+    LastTransfer,
+    FaucetClaims,
+    TransferLog,
+    StorageDeposits,
+    BalanceEntries,
+    AllowanceExpiry,
+    PermitKeys,
+    PermitNonces,
+    Minters,
+    NoDepositCallers,
+    AllowanceHistory,
+    RecoveryRequests,
+    AllowedByOwner,
+    Allowlist,
+    Denylist,
+    AccountHistory,
+    MintProposals,
+    ApprovedProtocols,
+    PendingMints,
+    AllowedReceivers,
+    MintRecords,
+    PeriodicAllowances,
+    WrappedBalances,
+    TransferCounts,
+    PeriodicAllowancesByOwner,
+    #[cfg_attr(feature = "lookup-balances", allow(dead_code))]
+    AccountIndex,
+    #[cfg_attr(feature = "lookup-balances", allow(dead_code))]
+    IndexedAccounts,
+    #[cfg_attr(not(feature = "lookup-balances"), allow(dead_code))]
+    RedenominatedAccounts,
+}
+
+mod uint_types {
+    #![allow(clippy::all)]
+    uint::construct_uint! {
+        pub struct U256(4);
+    }
+}
+
+const BPS_DENOMINATOR: u128 = 10_000;
+const DEFAULT_MAX_BATCH_SIZE: u32 = 100;
+const GAS_FOR_FT_ON_MINT: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_RESOLVE_MINT: Gas = Gas(5_000_000_000_000);
+const MAX_SYMBOL_LENGTH: usize = 16;
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+/// Each NEP is named by the module implementing it, so a module left out of
+/// the build takes its claim with it.
+const SUPPORTED_STANDARDS: &[&str] = &[
+    ft::FT_CORE_STANDARD,
+    storage::STORAGE_MANAGEMENT_STANDARD,
+    ft::FT_METADATA_STANDARD,
+    events::EVENTS_STANDARD,
+];
+
+#[ext_contract(ext_mint_receiver)]
+pub trait MintReceiver {
+    /// Returns the amount of the mint the receiver does not accept.
+    fn ft_on_mint(&mut self, amount: U128, msg: String) -> PromiseOrValue<U128>;
+}
+
+#[ext_contract(ext_mint_resolver)]
+pub trait MintResolver {
+    fn resolve_mint(&mut self, receiver_id: AccountId, amount: U128) -> U128;
+}
+
+fn assert_valid_name(name: &str) {
+    require!(!name.is_empty(), "name must not be empty");
+}
+
+fn assert_valid_symbol(symbol: &str) {
+    require!(!symbol.is_empty(), "symbol must not be empty");
+    require!(symbol.len() <= MAX_SYMBOL_LENGTH, "symbol too long");
+}
+
+/// Parses a decimal string such as `"1.5"` into base units at `decimals`.
+fn parse_display_amount(amount: &str, decimals: u8) -> u128 {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    require!(
+        !whole.is_empty()
+            && is_digits(whole)
+            && is_digits(fraction)
+            && !(fraction.is_empty() && amount.ends_with('.')),
+        "invalid display amount"
+    );
+    require!(
+        fraction.len() <= decimals as usize,
+        "display amount too precise"
+    );
+    let padded = format!("{whole}{fraction:0<width$}", width = decimals as usize);
+    padded.parse().expect("display amount overflow")
+}
+
+/// Inverse of `parse_display_amount`, without trailing fractional zeros.
+/// At zero decimals the result is a plain integer.
+fn format_display_amount(amount: u128, decimals: u8) -> String {
+    let unit = 10u128.pow(decimals.into());
+    let (whole, fraction) = (amount / unit, amount % unit);
+    if fraction == 0 {
+        return whole.to_string();
+    }
+    let fraction = format!("{fraction:0>width$}", width = decimals as usize);
+    format!("{whole}.{}", fraction.trim_end_matches('0'))
+}
+
+fn nested_key(prefix: &[u8], key: StorageKey) -> Vec<u8> {
+    [prefix, &key.into_storage_key()].concat()
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Reservation {
+    pub amount: u128,
+    pub deposit: Balance,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UpgradeRecord {
+    pub old_version: u32,
+    pub new_version: u32,
+    pub timestamp: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SupplyBreakdown {
+    pub total: U128,
+    pub circulating: U128,
+    pub locked: U128,
+    pub burned: U128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContractStats {
+    pub holders: u64,
+    pub total_supply: U128,
+    pub storage_usage: u64,
+    pub paused: bool,
+    pub block_timestamp: u64,
+}
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct ERC20 {
+    pub owner_id: AccountId,
     pub name: String,
     pub symbol: String,
     pub decimals: u8,
     pub total_supply: u128,
-    pub balance: UnorderedMap<AccountId, u128>,
+    pub event_standard: String,
+    pub event_version: String,
+    pub balance: balance::BalanceMap,
     pub allowed: UnorderedMap<AccountId, UnorderedMap<AccountId, u128>>,
+    pub reservations: UnorderedMap<AccountId, Reservation>,
+    pub pull_interval_secs: u64,
+    pub last_pull_ts: UnorderedMap<(AccountId, AccountId), u64>,
+    pub version: u32,
+    pub migrating: bool,
+    pub max_allowance: Option<u128>,
+    pub upgrade_history: Vector<UpgradeRecord>,
+    pub account_storage_usage: u64,
+    pub paused: bool,
+    pub frozen: UnorderedSet<AccountId>,
+    pub operators: UnorderedSet<(AccountId, AccountId)>,
+    pub transfer_cooldown_secs: u64,
+    pub faucet_enabled: bool,
+    pub faucet_amount: u128,
+    pub faucet_cooldown_secs: u64,
+    pub faucet_claims: UnorderedMap<AccountId, u64>,
+    pub transfer_log: Vector<transfer_log::TransferRecord>,
+    pub transfer_count: u64,
+    pub pauser_id: AccountId,
+    pub max_supply: Option<u128>,
+    pub emission: Option<emission::Emission>,
+    pub storage_deposits: UnorderedMap<AccountId, Balance>,
+    #[borsh_skip]
+    pub observers: Vec<Box<dyn observer::TransferObserver>>,
+    pub auto_register: bool,
+    pub return_false_on_unregistered: bool,
+    pub burn_fee_bps: u16,
+    pub minting_paused: bool,
+    pub allowance_expiry: UnorderedMap<(AccountId, AccountId), u64>,
+    pub min_balance: u128,
+    pub refund_storage_on_empty: bool,
+    pub permit_keys: UnorderedMap<AccountId, Vec<u8>>,
+    pub permit_nonces: UnorderedMap<AccountId, u64>,
+    pub minters: UnorderedSet<AccountId>,
+    pub no_deposit_callers: UnorderedSet<AccountId>,
+    pub min_fee: u128,
+    pub max_fee: Option<u128>,
+    pub max_batch_size: u32,
+    pub allowance_history:
+        UnorderedMap<(AccountId, AccountId), Vec<allowance_history::AllowanceChange>>,
+    pub global_hook: Option<AccountId>,
+    pub recovery_requests: UnorderedMap<u64, recovery::RecoveryRequest>,
+    pub next_recovery_id: u64,
+    pub storage_prefix: Vec<u8>,
+    pub transferable: bool,
+    pub total_burned: u128,
+    pub allowlist: UnorderedSet<AccountId>,
+    pub denylist: UnorderedSet<AccountId>,
+    pub whitelist_mode: bool,
+    pub referral_enabled: bool,
+    pub referral_bonus: u128,
+    pub referral_pool: u128,
+    pub all_frozen: bool,
+    pub batch_events: bool,
+    pub account_history: UnorderedMap<AccountId, Vec<account_history::AccountOperation>>,
+    pub mint_threshold: Option<u128>,
+    pub mint_approvals_required: u32,
+    pub mint_proposals: UnorderedMap<u64, multisig::MintProposal>,
+    pub next_mint_proposal_id: u64,
+    pub approved_protocols: UnorderedSet<AccountId>,
+    pub mint_requires_confirmation: bool,
+    pub pending_mints: UnorderedMap<u64, mint_confirmation::PendingMint>,
+    pub next_pending_mint_id: u64,
+    pub max_spenders_per_owner: Option<u32>,
+    pub min_supply: u128,
+    pub receiver_allowlist_enabled: bool,
+    pub allowed_receivers: UnorderedSet<AccountId>,
+    pub burn_address: Option<AccountId>,
+    pub clawback_window_secs: u64,
+    pub mint_records: UnorderedMap<u64, clawback::MintRecord>,
+    pub next_mint_record_id: u64,
+    pub holder_count: u64,
+    pub periodic_allowances:
+        UnorderedMap<AccountId, UnorderedMap<AccountId, periodic::PeriodicAllowance>>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<Vec<u8>>,
+    pub wrapped_supply: u128,
+    pub wrapped_balances: UnorderedMap<AccountId, u128>,
+    pub transfer_counts: UnorderedMap<AccountId, u64>,
+    #[cfg(not(feature = "lookup-balances"))]
+    pub account_index: Vector<AccountId>,
+    #[cfg(not(feature = "lookup-balances"))]
+    pub indexed_accounts: near_sdk::store::LookupSet<AccountId>,
+    pub redenomination: Option<redenomination::Redenomination>,
+    pub next_redenomination_id: u32,
+    pub total_storage_deposits: Balance,
+    pub total_reservation_deposits: Balance,
 }
 
 impl ERC20 {
-    pub fn init<B, A>(
+    /// `initial_balances` must sum to `total_supply`; an empty list leaves
+    /// the supply undistributed. `normalize_symbol` trims `name` and
+    /// `symbol` and uppercases `symbol`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn init<P>(
+        owner_id: AccountId,
         name: String,
         symbol: String,
         decimals: u8,
         total_supply: U128,
-        balance_prefix: B,
-        allowed_prefix: A,
+        event_standard: String,
+        event_version: String,
+        initial_balances: Vec<(AccountId, U128)>,
+        normalize_symbol: bool,
+        prefix: P,
     ) -> Self
     where
-        B: IntoStorageKey,
-        A: IntoStorageKey,
+        P: IntoStorageKey,
     {
-        Self {
+        let (name, symbol) = if normalize_symbol {
+            (name.trim().to_string(), symbol.trim().to_uppercase())
+        } else {
+            (name, symbol)
+        };
+        assert_valid_name(&name);
+        assert_valid_symbol(&symbol);
+        let prefix = prefix.into_storage_key();
+        let mut this = Self {
+            pauser_id: owner_id.clone(),
+            owner_id,
             name,
             symbol,
             decimals,
             total_supply: total_supply.into(),
-            balance: UnorderedMap::new(balance_prefix),
-            allowed: UnorderedMap::new(allowed_prefix),
+            event_standard,
+            event_version,
+            balance: balance::BalanceMap::new(nested_key(&prefix, StorageKey::BalanceEntries)),
+            allowed: UnorderedMap::new(nested_key(&prefix, StorageKey::Allowed)),
+            reservations: UnorderedMap::new(nested_key(&prefix, StorageKey::Reservations)),
+            pull_interval_secs: 0,
+            last_pull_ts: UnorderedMap::new(nested_key(&prefix, StorageKey::LastPull)),
+            version: 1,
+            migrating: false,
+            max_allowance: None,
+            upgrade_history: Vector::new(nested_key(&prefix, StorageKey::UpgradeHistory)),
+            account_storage_usage: 0,
+            paused: false,
+            frozen: UnorderedSet::new(nested_key(&prefix, StorageKey::Frozen)),
+            operators: UnorderedSet::new(nested_key(&prefix, StorageKey::Operators)),
+            transfer_cooldown_secs: 0,
+            faucet_enabled: false,
+            faucet_amount: 0,
+            faucet_cooldown_secs: 0,
+            faucet_claims: UnorderedMap::new(nested_key(&prefix, StorageKey::FaucetClaims)),
+            transfer_log: Vector::new(nested_key(&prefix, StorageKey::TransferLog)),
+            transfer_count: 0,
+            max_supply: None,
+            emission: None,
+            storage_deposits: UnorderedMap::new(nested_key(&prefix, StorageKey::StorageDeposits)),
+            observers: Vec::new(),
+            auto_register: true,
+            return_false_on_unregistered: false,
+            burn_fee_bps: 0,
+            minting_paused: false,
+            allowance_expiry: UnorderedMap::new(nested_key(&prefix, StorageKey::AllowanceExpiry)),
+            min_balance: 0,
+            refund_storage_on_empty: false,
+            permit_keys: UnorderedMap::new(nested_key(&prefix, StorageKey::PermitKeys)),
+            permit_nonces: UnorderedMap::new(nested_key(&prefix, StorageKey::PermitNonces)),
+            minters: UnorderedSet::new(nested_key(&prefix, StorageKey::Minters)),
+            no_deposit_callers: UnorderedSet::new(nested_key(
+                &prefix,
+                StorageKey::NoDepositCallers,
+            )),
+            min_fee: 0,
+            max_fee: None,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            allowance_history: UnorderedMap::new(nested_key(&prefix, StorageKey::AllowanceHistory)),
+            global_hook: None,
+            recovery_requests: UnorderedMap::new(nested_key(&prefix, StorageKey::RecoveryRequests)),
+            next_recovery_id: 0,
+            storage_prefix: prefix.clone(),
+            transferable: true,
+            total_burned: 0,
+            allowlist: UnorderedSet::new(nested_key(&prefix, StorageKey::Allowlist)),
+            denylist: UnorderedSet::new(nested_key(&prefix, StorageKey::Denylist)),
+            whitelist_mode: false,
+            referral_enabled: false,
+            referral_bonus: 0,
+            referral_pool: 0,
+            all_frozen: false,
+            batch_events: false,
+            account_history: UnorderedMap::new(nested_key(&prefix, StorageKey::AccountHistory)),
+            mint_threshold: None,
+            mint_approvals_required: 1,
+            mint_proposals: UnorderedMap::new(nested_key(&prefix, StorageKey::MintProposals)),
+            next_mint_proposal_id: 0,
+            approved_protocols: UnorderedSet::new(nested_key(
+                &prefix,
+                StorageKey::ApprovedProtocols,
+            )),
+            mint_requires_confirmation: false,
+            pending_mints: UnorderedMap::new(nested_key(&prefix, StorageKey::PendingMints)),
+            next_pending_mint_id: 0,
+            max_spenders_per_owner: None,
+            min_supply: 0,
+            receiver_allowlist_enabled: false,
+            allowed_receivers: UnorderedSet::new(nested_key(&prefix, StorageKey::AllowedReceivers)),
+            burn_address: None,
+            clawback_window_secs: 0,
+            mint_records: UnorderedMap::new(nested_key(&prefix, StorageKey::MintRecords)),
+            next_mint_record_id: 0,
+            holder_count: 0,
+            periodic_allowances: UnorderedMap::new(nested_key(
+                &prefix,
+                StorageKey::PeriodicAllowances,
+            )),
+            reference: None,
+            reference_hash: None,
+            wrapped_supply: 0,
+            wrapped_balances: UnorderedMap::new(nested_key(&prefix, StorageKey::WrappedBalances)),
+            transfer_counts: UnorderedMap::new(nested_key(&prefix, StorageKey::TransferCounts)),
+            #[cfg(not(feature = "lookup-balances"))]
+            account_index: Vector::new(nested_key(&prefix, StorageKey::AccountIndex)),
+            #[cfg(not(feature = "lookup-balances"))]
+            indexed_accounts: near_sdk::store::LookupSet::new(nested_key(
+                &prefix,
+                StorageKey::IndexedAccounts,
+            )),
+            redenomination: None,
+            next_redenomination_id: 0,
+            total_storage_deposits: 0,
+            total_reservation_deposits: 0,
+        };
+        this.measure_account_storage_usage();
+        if !initial_balances.is_empty() {
+            let sum = initial_balances
+                .iter()
+                .try_fold(0u128, |sum, (_, amount)| sum.checked_add(amount.0))
+                .expect("amount overflow");
+            require!(
+                sum == this.total_supply,
+                "initial balances must sum to total_supply"
+            );
+            for (account_id, amount) in initial_balances {
+                this.internal_credit(&account_id, amount.0);
+            }
         }
+        this
+    }
+
+    pub fn owner_id(&self) -> &AccountId {
+        &self.owner_id
+    }
+
+    fn assert_owner(&self) {
+        require!(predecessor_account_id() == self.owner_id, "only owner");
     }
 
     pub fn name(&self) -> &str {
@@ -48,238 +464,5894 @@ impl ERC20 {
         &self.symbol
     }
 
+    pub fn set_name(&mut self, new_name: String) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        assert_valid_name(&new_name);
+        self.name = new_name;
+        self.emit_metadata_update();
+    }
+
+    pub fn set_symbol(&mut self, new_symbol: String) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        assert_valid_symbol(&new_symbol);
+        self.symbol = new_symbol;
+        self.emit_metadata_update();
+    }
+
+    fn emit_metadata_update(&self) {
+        events::MetadataUpdate {
+            name: &self.name,
+            symbol: &self.symbol,
+        }
+        .emit(self);
+    }
+
     pub fn decimals(&self) -> &u8 {
         &self.decimals
     }
 
+    pub fn decimals_value(&self) -> u8 {
+        self.decimals
+    }
+
+    pub fn supported_standards(&self) -> Vec<String> {
+        SUPPORTED_STANDARDS
+            .iter()
+            .map(|standard| standard.to_string())
+            .collect()
+    }
+
     pub fn total_supply(&self) -> &u128 {
         &self.total_supply
     }
 
+    /// Lifetime amount destroyed by every burn path, transfer fees included.
+    pub fn total_burned(&self) -> U128 {
+        self.total_burned.into()
+    }
+
     pub fn balance_of(&self, account_id: AccountId) -> Option<&u128> {
-        self.balance.get(&account_id)
+        self.balance.get(&account_id).map(|entry| &entry.amount)
     }
 
-    pub fn transfer(&mut self, to: AccountId, value: U128) -> bool {
-        let user_balance = self.balance_of(predecessor_account_id()).unwrap_or(&0u128);
-        let value = value.into();
-        require!(*user_balance >= value);
-        self.balance
-            .insert(predecessor_account_id(), user_balance - value);
+    /// Share of the total supply held by `account_id`, in basis points.
+    pub fn percent_owned(&self, account_id: AccountId) -> u32 {
+        if self.total_supply == 0 {
+            return 0;
+        }
+        let balance = *self.balance_of(account_id).unwrap_or(&0u128);
+        (U256::from(balance) * U256::from(BPS_DENOMINATOR) / U256::from(self.total_supply)).as_u32()
+    }
+
+    /// Rescales the raw balance to `target_decimals`, truncating when
+    /// scaling down.
+    pub fn balance_of_scaled(&self, account_id: AccountId, target_decimals: u8) -> U128 {
+        let balance = U256::from(*self.balance_of(account_id).unwrap_or(&0u128));
+        let scaled = if target_decimals >= self.decimals {
+            balance * U256::exp10((target_decimals - self.decimals).into())
+        } else {
+            balance / U256::exp10((self.decimals - target_decimals).into())
+        };
+        require!(scaled <= U256::from(u128::MAX), "scaled balance overflow");
+        scaled.as_u128().into()
+    }
+
+    #[cfg(not(feature = "lookup-balances"))]
+    /// `circulating` is the supply not held under per-account locks. Summing
+    /// the locks scans every holder.
+    pub fn supply_breakdown(&self) -> SupplyBreakdown {
+        let locked = self
+            .balance
+            .values()
+            .fold(0u128, |locked, entry| locked.saturating_add(entry.locked));
+        SupplyBreakdown {
+            total: self.total_supply.into(),
+            circulating: self.total_supply.saturating_sub(locked).into(),
+            locked: locked.into(),
+            burned: self.total_burned.into(),
+        }
+    }
+
+    /// `holders` counts accounts with a non-zero balance.
+    pub fn contract_stats(&self) -> ContractStats {
+        ContractStats {
+            holders: self.holder_count,
+            total_supply: self.total_supply.into(),
+            storage_usage: env::storage_usage(),
+            paused: self.paused,
+            block_timestamp: env::block_timestamp(),
+        }
+    }
+
+    #[cfg(not(feature = "lookup-balances"))]
+    /// Sums the balances of up to `limit` indexed accounts starting at
+    /// `from_index` and returns the sum with the index to resume from, so an
+    /// audit of `total_supply` can be spread over several calls. The index
+    /// equals the length of the account index once every account was visited.
+    pub fn audit_supply_partial(&self, from_index: u64, limit: u64) -> (U128, u64) {
+        let len = self.account_index.len() as u64;
+        let from_index = from_index.min(len);
+        let next_index = from_index.saturating_add(limit).min(len);
+        let mut sum = 0u128;
+        for index in from_index..next_index {
+            let account_id = &self.account_index[index as u32];
+            let amount = self.balance.get(account_id).map_or(0, |entry| entry.amount);
+            sum = sum.checked_add(amount).expect("amount overflow");
+        }
+        (sum.into(), next_index)
+    }
 
-        let mut receiver_balance = self.balance_of(to.clone()).unwrap_or(&0u128);
-        if let 0 = receiver_balance {
-            self.balance.insert(predecessor_account_id().clone(), 0u128);
-            receiver_balance = &0u128;
+    #[cfg(not(feature = "lookup-balances"))]
+    /// Scans every holder and allowance for broken invariants and describes
+    /// each one found; an empty list means none were found. Supply left
+    /// undistributed by `init` is not held by any account, so only balances
+    /// exceeding `total_supply` are reported.
+    pub fn self_check(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut sum = 0u128;
+        for (account_id, entry) in self.balance.iter() {
+            match sum.checked_add(entry.amount) {
+                Some(total) => sum = total,
+                None => problems.push("sum of balances overflows".to_string()),
+            }
+            if entry.locked > entry.amount {
+                problems.push(format!(
+                    "{account_id} has {} locked but a balance of {}",
+                    entry.locked, entry.amount
+                ));
+            }
+        }
+        if sum > self.total_supply {
+            problems.push(format!(
+                "balances sum to {sum}, above total_supply {}",
+                self.total_supply
+            ));
+        }
+        for (owner_id, allowed) in self.allowed.iter() {
+            if allowed.is_empty() {
+                problems.push(format!("allowance map of {owner_id} has no spenders"));
+            }
+        }
+        for (owner_id, spender_id) in self.allowance_expiry.keys() {
+            let allowance_exists = self
+                .allowed
+                .get(owner_id)
+                .is_some_and(|allowed| allowed.contains_key(spender_id));
+            if !allowance_exists {
+                problems.push(format!(
+                    "expiry of {owner_id} -> {spender_id} has no allowance"
+                ));
+            }
         }
+        problems
+    }
 
-        self.balance.insert(to, receiver_balance + value);
+    pub fn transfer(&mut self, to: AccountId, value: U128) -> bool {
+        self.assert_not_migrating();
+        if self.return_false_on_unregistered && !self.is_registered(&to) {
+            return false;
+        }
+        let sender = predecessor_account_id();
+        self.internal_check_cooldown(&sender);
+        self.internal_transfer(&sender, &to, value.into());
 
         true
     }
 
+    /// Sends `amount` to `to` and `tip_amount` to `tip_recipient`; the
+    /// caller's unlocked balance must cover both.
+    pub fn transfer_with_tip(
+        &mut self,
+        to: AccountId,
+        amount: U128,
+        tip_recipient: AccountId,
+        tip_amount: U128,
+    ) {
+        self.assert_not_migrating();
+        let sender = predecessor_account_id();
+        let total = amount.0.checked_add(tip_amount.0).expect("amount overflow");
+        let balance = *self.balance_of(sender.clone()).unwrap_or(&0u128);
+        require!(balance >= total, "insufficient balance");
+        require!(
+            balance - total >= self.locked_of(sender.clone()).0,
+            "balance is locked"
+        );
+        self.internal_check_cooldown(&sender);
+        for (recipient, value) in [(&to, amount.0), (&tip_recipient, tip_amount.0)] {
+            self.internal_transfer(&sender, recipient, value);
+        }
+    }
+
+    /// Sends the caller's whole unlocked balance and returns the amount
+    /// debited, burn fee included.
+    pub fn transfer_all(&mut self, to: AccountId) -> U128 {
+        self.assert_not_migrating();
+        let sender = predecessor_account_id();
+        let balance = *self.balance_of(sender.clone()).unwrap_or(&0u128);
+        let spendable = balance.saturating_sub(self.locked_of(sender.clone()).0);
+        require!(spendable > 0, "nothing to transfer");
+        self.internal_check_cooldown(&sender);
+        self.internal_transfer(&sender, &to, spendable);
+        spendable.into()
+    }
+
+    /// `transfer` with the amount given as a decimal string in whole tokens,
+    /// e.g. `"1.5"`.
+    pub fn transfer_display(&mut self, to: AccountId, amount: String) -> bool {
+        let value = self.from_display_amount(amount);
+        self.transfer(to, value)
+    }
+
+    /// Formats base units as whole tokens, e.g. `"1.5"`.
+    pub fn to_display_amount(&self, amount: U128) -> String {
+        format_display_amount(amount.0, self.decimals)
+    }
+
+    /// Parses whole tokens into base units; at zero decimals only integers
+    /// are accepted.
+    pub fn from_display_amount(&self, amount: String) -> U128 {
+        parse_display_amount(&amount, self.decimals).into()
+    }
+
+    /// With `auto_register` off, transfers to accounts without a balance
+    /// entry panic, or make `transfer` return `false` when
+    /// `return_false_on_unregistered` is set.
+    pub fn set_registration_policy(
+        &mut self,
+        auto_register: bool,
+        return_false_on_unregistered: bool,
+    ) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.auto_register = auto_register;
+        self.return_false_on_unregistered = !auto_register && return_false_on_unregistered;
+    }
+
+    /// Transfers may not leave a sender with a non-zero balance below
+    /// `min_balance`. While it is set, a sender emptied by a transfer is
+    /// unregistered.
+    pub fn set_min_balance(&mut self, min_balance: U128) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.min_balance = min_balance.0;
+    }
+
+    fn is_registered(&self, account_id: &AccountId) -> bool {
+        self.auto_register || self.balance.contains_key(account_id)
+    }
+
     pub fn transfer_from(&mut self, from: AccountId, to: AccountId, value: U128) -> bool {
-        let user_balance = self.balance_of(from.clone()).unwrap();
+        self.assert_not_migrating();
         let value = value.into();
-        require!(*user_balance >= value);
-        require!(self.allowance(from.clone(), predecessor_account_id()) >= &value);
-        self.balance.insert(from, user_balance - value).unwrap();
+        require!(
+            *self.balance_of(from.clone()).unwrap_or(&0u128) >= value,
+            "insufficient balance"
+        );
+        let spender = predecessor_account_id();
+        if self.is_operator(from.clone(), spender.clone())
+            || self.approved_protocols.contains(&spender)
+        {
+            events::OperatorTransfer {
+                operator_id: &spender,
+                owner_id: &from,
+                receiver_id: &to,
+                amount: value.into(),
+            }
+            .emit(self);
+        } else if self
+            .periodic_allowances
+            .get(&from)
+            .is_some_and(|allowances| allowances.contains_key(&spender))
+        {
+            self.internal_spend_periodic_allowance(&from, &spender, value);
+        } else {
+            self.internal_spend_allowance(&from, &spender, value);
+        }
+        self.internal_check_cooldown(&from);
+        self.internal_transfer(&from, &to, value);
+
+        true
+    }
+
+    /// Operators may move any amount of the caller's tokens through
+    /// `transfer_from` without an allowance.
+    pub fn set_operator(&mut self, operator: AccountId, approved: bool) {
+        self.assert_not_migrating();
+        let owner_id = predecessor_account_id();
+        let key = (owner_id.clone(), operator.clone());
+        if approved {
+            self.operators.insert(key);
+        } else {
+            self.operators.remove(&key);
+        }
+        events::OperatorUpdate {
+            owner_id: &owner_id,
+            operator_id: &operator,
+            approved,
+        }
+        .emit(self);
+    }
 
-        let mut receiver_balance = self.balance_of(to.clone()).unwrap_or(&0u128);
-        if let 0 = receiver_balance {
-            self.balance.insert(predecessor_account_id().clone(), 0u128);
-            receiver_balance = &0u128;
+    /// Approved protocols act as an operator for every holder.
+    pub fn set_approved_protocol(&mut self, protocol: AccountId, approved: bool) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        if approved {
+            self.approved_protocols.insert(protocol);
+        } else {
+            self.approved_protocols.remove(&protocol);
         }
+    }
+
+    pub fn is_approved_protocol(&self, protocol: AccountId) -> bool {
+        self.approved_protocols.contains(&protocol)
+    }
 
-        self.balance.insert(to, receiver_balance + value).unwrap();
+    pub fn is_operator(&self, owner: AccountId, operator: AccountId) -> bool {
+        self.operators.contains(&(owner, operator))
+    }
 
-        true
+    /// Caps the number of entries any batch method accepts.
+    pub fn set_max_batch_size(&mut self, max_batch_size: u32) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.max_batch_size = max_batch_size;
     }
 
-    pub fn approve(&mut self, spender: AccountId, value: U128) {
-        let allowance_exist = self.allowed.contains_key(&predecessor_account_id());
-        if let false = allowance_exist {
-            self.allowed.insert(
-                predecessor_account_id(),
-                UnorderedMap::new(near_sdk::env::keccak256(spender.as_bytes())),
+    fn assert_batch_size(&self, len: usize) {
+        require!(
+            len <= self.max_batch_size as usize,
+            format!("batch exceeds max size of {}", self.max_batch_size)
+        );
+    }
+
+    /// Sends every amount from the caller. All recipients and the combined
+    /// total are validated before any balance changes.
+    pub fn batch_transfer(&mut self, recipients: Vec<(AccountId, U128)>) {
+        self.assert_not_migrating();
+        self.assert_batch_size(recipients.len());
+        let sender = predecessor_account_id();
+        require!(!self.paused, "contract is paused");
+        require!(
+            !self.frozen.contains(&sender)
+                && recipients
+                    .iter()
+                    .all(|(recipient, _)| !self.frozen.contains(recipient)),
+            "account is frozen"
+        );
+        require!(
+            recipients
+                .iter()
+                .all(|(recipient, _)| self.is_registered(recipient)),
+            "receiver not registered"
+        );
+        let total = recipients
+            .iter()
+            .try_fold(0u128, |total, (_, amount)| total.checked_add(amount.0))
+            .expect("amount overflow");
+        let balance = *self.balance_of(sender.clone()).unwrap_or(&0u128);
+        require!(balance >= total, "insufficient balance");
+        require!(
+            balance - total >= self.locked_of(sender.clone()).0,
+            "balance is locked"
+        );
+
+        self.internal_check_cooldown(&sender);
+        let delivered: Vec<u128> = recipients
+            .iter()
+            .map(|(recipient, amount)| {
+                self.internal_transfer_unlogged(&sender, recipient, amount.0)
+            })
+            .collect();
+        let events: Vec<events::FtTransfer> = recipients
+            .iter()
+            .zip(delivered)
+            .filter(|((recipient, _), _)| !self.is_burn_address(recipient))
+            .map(|((recipient, _), amount)| events::FtTransfer {
+                old_owner_id: &sender,
+                new_owner_id: recipient,
+                amount: amount.into(),
+                correlation_id: None,
+            })
+            .collect();
+        self.emit_batch(&events, events::FtTransfer::emit_many);
+    }
+
+    /// In `batch_events` mode a batch logs one event carrying every
+    /// movement instead of one event per movement.
+    pub fn set_batch_events(&mut self, batch_events: bool) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.batch_events = batch_events;
+    }
+
+    fn emit_batch<T>(&self, events: &[T], emit_many: fn(&[T], &ERC20)) {
+        if self.batch_events {
+            emit_many(events, self);
+        } else {
+            for event in events {
+                emit_many(std::slice::from_ref(event), self);
+            }
+        }
+    }
+
+    /// Splits `total` between recipients by basis-point weights summing to
+    /// 10000. Rounding dust goes to the last recipient.
+    pub fn split_transfer(&mut self, recipients: Vec<(AccountId, u32)>, total: U128) {
+        self.assert_not_migrating();
+        self.assert_batch_size(recipients.len());
+        let weight_sum: u128 = recipients.iter().map(|(_, weight)| *weight as u128).sum();
+        require!(weight_sum == BPS_DENOMINATOR, "weights must sum to 10000");
+
+        let sender = predecessor_account_id();
+        self.internal_check_cooldown(&sender);
+        let mut remaining = total.0;
+        let last = recipients.len() - 1;
+        for (index, (recipient, weight)) in recipients.iter().enumerate() {
+            let share = if index == last {
+                remaining
+            } else {
+                (U256::from(total.0) * U256::from(*weight) / U256::from(BPS_DENOMINATOR)).as_u128()
+            };
+            remaining -= share;
+            self.internal_transfer(&sender, recipient, share);
+        }
+    }
+
+    /// Lets an approved spender pull from `from` at most once per
+    /// `pull_interval_secs`, spending the allowance like `transfer_from`.
+    pub fn pull_payment(&mut self, from: AccountId, value: U128) -> bool {
+        self.assert_not_migrating();
+        let spender = predecessor_account_id();
+        let key = (from.clone(), spender.clone());
+        let now = env::block_timestamp();
+        if let Some(last_pull_ts) = self.last_pull_ts.get(&key) {
+            require!(
+                now >= last_pull_ts + self.pull_interval_secs * NANOS_PER_SEC,
+                "pull interval not elapsed"
             );
         }
 
-        self.allowed
-            .get_mut(&predecessor_account_id())
-            .unwrap()
-            .insert(spender, value.into());
+        let value = value.into();
+        self.internal_spend_allowance(&from, &spender, value);
+        self.internal_check_cooldown(&from);
+        self.internal_transfer(&from, &spender, value);
+        self.last_pull_ts.insert(key, now);
+
+        true
     }
 
-    pub fn allowance(&self, owner: AccountId, spender: AccountId) -> &u128 {
-        self.allowed.get(&owner).unwrap().get(&spender).unwrap()
+    pub fn set_pull_interval(&mut self, secs: u64) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.pull_interval_secs = secs;
     }
 
-    pub fn mint(&mut self, to: AccountId, value: U128) {
-        log!("key exist: {}", self.balance.contains_key(&to));
-        log!("Balance: {:?}", self.balance.get(&to));
-        if let false = self.balance.contains_key(&to) {
-            self.balance.insert(to.clone(), value.0);
-            return;
-        }
-        let temp = self.balance.get(&to).expect("get failed");
-        *self.balance.get_mut(&to).expect("get_mut failed") = value.0 + temp;
+    /// Blocks every mutating method until `migrate` completes, for
+    /// migrations that span several calls.
+    pub fn begin_migration(&mut self) {
+        self.assert_owner();
+        self.migrating = true;
     }
 
-    pub fn burn(&mut self, account_id: AccountId, value: U128) {
-        require!(value.0 != 0);
-        require!(*self.balance_of(account_id.clone()).unwrap_or(&0u128) >= value.0);
-        *self.balance.get_mut(&account_id).unwrap() -= value.0;
+    /// Records a state upgrade. The embedding contract calls this from its
+    /// own `migrate` entrypoint after rewriting state for `new_version`.
+    pub fn migrate(&mut self, new_version: u32) {
+        self.assert_owner();
+        require!(self.redenomination.is_none(), "redenomination in progress");
+        require!(new_version > self.version, "version must increase");
+        self.migrating = true;
+        self.upgrade_history.push(UpgradeRecord {
+            old_version: self.version,
+            new_version,
+            timestamp: env::block_timestamp(),
+        });
+        self.version = new_version;
+        self.migrating = false;
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::*;
-    use near_sdk::{test_utils::VMContextBuilder, testing_env, BorshStorageKey};
+    fn assert_not_migrating(&self) {
+        require!(!self.migrating, "contract is migrating");
+    }
 
-    const DECIMALS: u8 = 18;
-    const TOTAL_SUPPLY: u128 = 10u128.pow(9);
+    pub fn upgrade_history(&self) -> Vec<UpgradeRecord> {
+        self.upgrade_history.iter().cloned().collect()
+    }
 
-    #[derive(BorshSerialize, BorshDeserialize, BorshStorageKey)]
-    enum StorageKey {
-        Balance,
-        Allowed,
+    /// How much `account_id` could send right now, taking pause, freeze
+    /// and lock restrictions into account.
+    pub fn spendable_balance(&self, account_id: AccountId) -> U128 {
+        if self.paused || !self.transferable || self.all_frozen || self.frozen.contains(&account_id)
+        {
+            return 0.into();
+        }
+        let balance = *self.balance_of(account_id.clone()).unwrap_or(&0u128);
+        balance.saturating_sub(self.locked_of(account_id).0).into()
     }
 
-    fn get_context(predecessor: String) -> VMContextBuilder {
-        let mut builder = VMContextBuilder::new();
-        builder.predecessor_account_id(predecessor.parse().unwrap());
-        builder
+    pub fn pause(&mut self) {
+        self.assert_pauser();
+        self.paused = true;
     }
 
-    #[test]
-    fn test_approve() {
-        let predecessor = "nutinaguti.testnet".parse().unwrap();
-        let context = get_context(predecessor);
-        testing_env!(context.build());
+    pub fn unpause(&mut self) {
+        self.assert_pauser();
+        self.paused = false;
+    }
 
-        let mut contract = ERC20::init(
-            "FUN COIN".to_string(),
-            "FUNC".to_string(),
-            DECIMALS,
-            TOTAL_SUPPLY.into(),
-            StorageKey::Balance,
-            StorageKey::Allowed,
-        );
-        contract.approve("test.testnet".parse().unwrap(), 1.into());
-        let allowance = contract.allowance(
-            "nutinaguti.testnet".parse().unwrap(),
-            "test.testnet".parse().unwrap(),
-        );
-        assert_eq!(1, *allowance);
+    /// Stops issuance through `mint` while transfers keep working.
+    pub fn pause_minting(&mut self) {
+        self.assert_pauser();
+        self.minting_paused = true;
+    }
 
-        contract.approve("test.testnet".parse().unwrap(), 2.into());
-        let allowance = contract.allowance(
-            "nutinaguti.testnet".parse().unwrap(),
-            "test.testnet".parse().unwrap(),
-        );
-        assert_eq!(2, *allowance);
+    pub fn unpause_minting(&mut self) {
+        self.assert_pauser();
+        self.minting_paused = false;
     }
 
-    #[test]
-    #[should_panic]
-    fn test_transfer_negative() {
-        let predecessor = "nutinaguti.testnet".parse().unwrap();
-        let context = get_context(predecessor);
-        testing_env!(context.build());
+    /// Hands the pause key to a separate account; starts out as the owner.
+    pub fn set_pauser(&mut self, pauser_id: AccountId) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.pauser_id = pauser_id;
+    }
 
-        let mut contract = ERC20::init(
-            "FUN COIN".to_string(),
-            "FUNC".to_string(),
-            DECIMALS,
-            TOTAL_SUPPLY.into(),
-            StorageKey::Balance,
-            StorageKey::Allowed,
-        );
-        contract.transfer("test.testnet".parse().unwrap(), 1.into());
+    pub fn pauser_id(&self) -> &AccountId {
+        &self.pauser_id
     }
 
-    #[test]
-    fn test_transfer_positive() {
-        let predecessor = "nutinaguti.testnet".parse().unwrap();
-        let context = get_context(predecessor);
-        testing_env!(context.build());
+    fn assert_pauser(&self) {
+        require!(predecessor_account_id() == self.pauser_id, "only pauser");
+    }
 
-        let mut contract = ERC20::init(
-            "FUN COIN".to_string(),
-            "FUNC".to_string(),
-            DECIMALS,
-            TOTAL_SUPPLY.into(),
-            StorageKey::Balance,
-            StorageKey::Allowed,
-        );
-        contract.mint("nutinaguti.testnet".parse().unwrap(), 1.into());
-        contract.transfer("test.testnet".parse().unwrap(), 1.into());
-        assert_eq!(
-            0u128,
-            *contract
-                .balance_of("nutinaguti.testnet".parse().unwrap())
-                .unwrap()
-        );
-        assert_eq!(
-            1u128,
-            *contract
-                .balance_of("test.testnet".parse().unwrap())
-                .unwrap()
-        );
+    pub fn is_paused(&self) -> bool {
+        self.paused
     }
 
-    #[test]
-    #[should_panic]
-    fn test_transfer_from_negative() {
-        let predecessor = "nutinaguti.testnet".parse().unwrap();
-        let context = get_context(predecessor);
-        testing_env!(context.build());
+    pub fn freeze(&mut self, account_id: AccountId) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.frozen.insert(account_id);
+    }
 
-        let mut contract = ERC20::init(
-            "FUN COIN".to_string(),
-            "FUNC".to_string(),
-            DECIMALS,
-            TOTAL_SUPPLY.into(),
-            StorageKey::Balance,
-            StorageKey::Allowed,
-        );
-        contract.mint("test.testnet".parse().unwrap(), 1.into());
-        contract.transfer_from(
-            "test.testnet".parse().unwrap(),
-            "nutinaguti.testnet".parse().unwrap(),
-            1.into(),
-        );
+    pub fn unfreeze(&mut self, account_id: AccountId) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.frozen.remove(&account_id);
     }
 
-    #[test]
-    fn test_transfer_from_positive() {
-        let predecessor = "nutinaguti.testnet".parse().unwrap();
-        let context = get_context(predecessor);
+    /// Freezes every account at once, independent of the per-account list.
+    pub fn emergency_freeze_all(&mut self) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.all_frozen = true;
+    }
 
-        let predecessor_2 = "test.testnet".parse().unwrap();
-        let context_2 = get_context(predecessor_2);
-        testing_env!(context.build());
+    pub fn emergency_unfreeze_all(&mut self) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.all_frozen = false;
+    }
 
-        let mut contract = ERC20::init(
-            "FUN COIN".to_string(),
-            "FUNC".to_string(),
-            DECIMALS,
-            TOTAL_SUPPLY.into(),
-            StorageKey::Balance,
-            StorageKey::Allowed,
-        );
-        contract.mint("test.testnet".parse().unwrap(), 1.into());
+    pub fn is_frozen(&self, account_id: AccountId) -> bool {
+        self.all_frozen || self.frozen.contains(&account_id)
+    }
 
-        testing_env!(context_2.build());
-        contract.approve("nutinaguti.testnet".parse().unwrap(), 1.into());
-        testing_env!(context.build());
+    /// Locks `amount` of the account's balance against transfers; zero
+    /// removes the lock.
+    pub fn set_locked(&mut self, account_id: AccountId, amount: U128) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        match self.balance.get_mut(&account_id) {
+            Some(entry) => entry.locked = amount.0,
+            None if amount.0 > 0 => {
+                self.balance.insert(
+                    account_id.clone(),
+                    balance::BalanceEntry {
+                        locked: amount.0,
+                        ..Default::default()
+                    },
+                );
+                self.internal_index_account(&account_id);
+            }
+            None => {}
+        }
+    }
 
-        contract.transfer_from(
-            "test.testnet".parse().unwrap(),
-            "nutinaguti.testnet".parse().unwrap(),
-            1.into(),
-        );
+    pub fn locked_of(&self, account_id: AccountId) -> U128 {
+        self.balance
+            .get(&account_id)
+            .map_or(0, |entry| entry.locked)
+            .into()
+    }
+
+    /// Whether `transfer` of `amount` from `from` to `to` would currently
+    /// succeed, so frontends can disable the action up front.
+    pub fn can_transfer(&self, from: AccountId, to: AccountId, amount: U128) -> bool {
+        if self.migrating || self.check_transfer(&from, &to, amount.0).is_err() {
+            return false;
+        }
+        if self.transfer_cooldown_secs > 0 {
+            let last_transfer_ts = self
+                .balance
+                .get(&from)
+                .and_then(|entry| entry.last_transfer_ts);
+            if let Some(last_transfer_ts) = last_transfer_ts {
+                let now = env::block_timestamp();
+                if now < last_transfer_ts + self.transfer_cooldown_secs * NANOS_PER_SEC {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    pub fn set_transfer_cooldown(&mut self, secs: u64) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.transfer_cooldown_secs = secs;
+    }
+
+    /// Enforces `transfer_cooldown_secs` between outbound transfers of
+    /// `account_id`. Called once per user operation, not per movement.
+    fn internal_check_cooldown(&mut self, account_id: &AccountId) {
+        if self.transfer_cooldown_secs == 0 {
+            return;
+        }
+        let now = env::block_timestamp();
+        let last_transfer_ts = self
+            .balance
+            .get(account_id)
+            .and_then(|entry| entry.last_transfer_ts);
+        if let Some(last_transfer_ts) = last_transfer_ts {
+            require!(
+                now >= last_transfer_ts + self.transfer_cooldown_secs * NANOS_PER_SEC,
+                "transfer cooldown active"
+            );
+        }
+        if !self.balance.contains_key(account_id) {
+            self.internal_set_balance(account_id, 0);
+        }
+        self.balance.get_mut(account_id).unwrap().last_transfer_ts = Some(now);
+    }
+
+    /// Moves the caller's whole unlocked balance to `to`. Unlike other
+    /// transfers this keeps working while the contract is paused.
+    pub fn emergency_withdraw(&mut self, to: AccountId) -> U128 {
+        self.assert_not_migrating();
+        require!(self.transferable, "token is non-transferable");
+        let account_id = predecessor_account_id();
+        let balance = *self.balance_of(account_id.clone()).unwrap_or(&0u128);
+        let amount = balance.saturating_sub(self.locked_of(account_id.clone()).0);
+        self.internal_move(&account_id, &to, amount);
+        amount.into()
+    }
+
+    /// Burns `burn_fee_bps` of every transfer from the sender in addition to
+    /// delivering the rest.
+    pub fn set_burn_fee(&mut self, bps: u16) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        require!(
+            u128::from(bps) <= BPS_DENOMINATOR,
+            "burn fee exceeds 10000 bps"
+        );
+        self.burn_fee_bps = bps;
+    }
+
+    /// Clamps a non-zero burn fee to `[min_fee, max_fee]`; the fee never
+    /// exceeds the transferred amount.
+    pub fn set_burn_fee_bounds(&mut self, min_fee: U128, max_fee: Option<U128>) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        let max_fee = max_fee.map(|max| max.0);
+        require!(
+            max_fee.is_none_or(|max_fee| min_fee.0 <= max_fee),
+            "min fee exceeds max fee"
+        );
+        self.min_fee = min_fee.0;
+        self.max_fee = max_fee;
+    }
+
+    /// Burns may not take `total_supply` below `min_supply`: `burn`,
+    /// `burn_all` and `batch_burn` revert, and a transfer whose burn fee
+    /// would cross it is delivered in full with a `burn_skipped` event.
+    pub fn set_min_supply(&mut self, min_supply: U128) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.min_supply = min_supply.0;
+    }
+
+    fn assert_burn_above_min_supply(&self, amount: u128) {
+        require!(
+            self.total_supply.saturating_sub(amount) >= self.min_supply,
+            "burn below min supply"
+        );
+    }
+
+    fn burn_fee(&self, value: u128) -> u128 {
+        if self.burn_fee_bps == 0 {
+            return 0;
+        }
+        let fee = (U256::from(value) * U256::from(self.burn_fee_bps) / U256::from(BPS_DENOMINATOR))
+            .as_u128()
+            .max(self.min_fee);
+        fee.min(self.max_fee.unwrap_or(u128::MAX)).min(value)
+    }
+
+    /// Soulbound mode: while false, holders cannot move tokens, but minting
+    /// and burning keep working.
+    pub fn set_transferable(&mut self, transferable: bool) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.transferable = transferable;
+    }
+
+    /// Transfers to the burn address burn the tokens instead of crediting
+    /// it; `None` disables this.
+    pub fn set_burn_address(&mut self, burn_address: Option<AccountId>) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.burn_address = burn_address;
+    }
+
+    /// Returns the amount delivered to `to` after the burn fee, zero when
+    /// `to` is the burn address.
+    fn internal_transfer(&mut self, from: &AccountId, to: &AccountId, value: u128) -> u128 {
+        self.internal_transfer_with_correlation(from, to, value, None)
+    }
+
+    /// `internal_transfer` with an optional correlation id echoed in the
+    /// `ft_transfer` event. Transfers to the burn address log `ft_burn`
+    /// instead.
+    fn internal_transfer_with_correlation(
+        &mut self,
+        from: &AccountId,
+        to: &AccountId,
+        value: u128,
+        correlation_id: Option<&str>,
+    ) -> u128 {
+        let delivered = self.internal_transfer_unlogged(from, to, value);
+        if !self.is_burn_address(to) {
+            events::FtTransfer {
+                old_owner_id: from,
+                new_owner_id: to,
+                amount: delivered.into(),
+                correlation_id,
+            }
+            .emit(self);
+        }
+        delivered
+    }
+
+    /// Transfer without the `ft_transfer` event, for callers that log the
+    /// movement themselves.
+    fn internal_transfer_unlogged(
+        &mut self,
+        from: &AccountId,
+        to: &AccountId,
+        value: u128,
+    ) -> u128 {
+        if let Err(error) = self.check_transfer(from, to, value) {
+            require!(false, error);
+        }
+        if self.is_burn_address(to) {
+            self.internal_move_with_fee(from, to, value, 0);
+            self.internal_count_transfer(from);
+            return 0;
+        }
+        let mut fee = self.burn_fee(value);
+        if fee > 0 && self.total_supply.saturating_sub(fee) < self.min_supply {
+            events::BurnSkipped {
+                owner_id: from,
+                amount: fee.into(),
+            }
+            .emit(self);
+            fee = 0;
+        }
+        self.internal_move_with_fee(from, to, value, fee);
+        self.internal_count_transfer(from);
+        value - fee
+    }
+
+    fn internal_count_transfer(&mut self, account_id: &AccountId) {
+        *self.transfer_counts.entry(account_id.clone()).or_default() += 1;
+    }
+
+    fn is_burn_address(&self, account_id: &AccountId) -> bool {
+        self.burn_address.as_ref() == Some(account_id)
+    }
+
+    /// `check_move` plus the rules that only apply to user transfers.
+    fn check_transfer(
+        &self,
+        from: &AccountId,
+        to: &AccountId,
+        value: u128,
+    ) -> Result<(), &'static str> {
+        if self.paused {
+            return Err("contract is paused");
+        }
+        if !self.transferable {
+            return Err("token is non-transferable");
+        }
+        self.check_move(from, to, value)
+    }
+
+    /// Rules shared by every path that moves balances; `can_transfer`
+    /// evaluates the same ones. The burn address needs no registration
+    /// since nothing is credited to it.
+    fn check_move(
+        &self,
+        from: &AccountId,
+        to: &AccountId,
+        value: u128,
+    ) -> Result<(), &'static str> {
+        self.check_receive(to)?;
+        if self.frozen.contains(from) {
+            return Err("account is frozen");
+        }
+        let from_balance = *self.balance_of(from.clone()).unwrap_or(&0u128);
+        if from_balance < value {
+            return Err("insufficient balance");
+        }
+        let remaining = from_balance - value;
+        if remaining < self.locked_of(from.clone()).0 {
+            return Err("balance is locked");
+        }
+        if remaining != 0 && remaining < self.min_balance {
+            return Err("balance below minimum");
+        }
+        Ok(())
+    }
+
+    /// The rules `check_move` applies to the receiving side.
+    fn check_receive(&self, to: &AccountId) -> Result<(), &'static str> {
+        if self.all_frozen {
+            return Err("all accounts are frozen");
+        }
+        if !self.can_receive(to) {
+            return Err("receiver not allowed");
+        }
+        if *to == env::current_account_id() {
+            return Err("cannot transfer to token contract");
+        }
+        if !self.is_burn_address(to) && !self.is_registered(to) {
+            return Err("receiver not registered");
+        }
+        if self.frozen.contains(to) {
+            return Err("account is frozen");
+        }
+        Ok(())
+    }
+
+    /// Fee-free move that logs `ft_transfer` itself, for the paths outside
+    /// the regular transfer methods.
+    fn internal_move(&mut self, from: &AccountId, to: &AccountId, value: u128) {
+        self.internal_move_with_fee(from, to, value, 0);
+        if !self.is_burn_address(to) {
+            events::FtTransfer {
+                old_owner_id: from,
+                new_owner_id: to,
+                amount: value.into(),
+                correlation_id: None,
+            }
+            .emit(self);
+        }
+    }
+
+    /// Debits `value` from `from`, delivers `value - fee` to `to` and burns
+    /// `fee`, checking the lock and `min_balance` against the full debit.
+    /// Moving to the burn address burns the delivered part as well.
+    fn internal_move_with_fee(&mut self, from: &AccountId, to: &AccountId, value: u128, fee: u128) {
+        if let Err(error) = self.check_move(from, to, value) {
+            require!(false, error);
+        }
+        let remaining = *self.balance_of(from.clone()).unwrap_or(&0u128) - value;
+        // The entry carries the cooldown timestamp, so it stays while a
+        // cooldown is configured.
+        if remaining == 0
+            && self.min_balance > 0
+            && self.locked_of(from.clone()).0 == 0
+            && self.transfer_cooldown_secs == 0
+        {
+            self.internal_remove_balance(from);
+        } else {
+            self.internal_set_balance(from, remaining);
+        }
+
+        let delivered = value - fee;
+        if self.is_burn_address(to) {
+            self.assert_burn_above_min_supply(delivered);
+            self.total_supply -= delivered;
+            self.total_burned += delivered;
+            self.record_account_operation(from, account_history::OpType::Burn, None, delivered);
+            events::FtBurn {
+                owner_id: from,
+                amount: delivered.into(),
+            }
+            .emit(self);
+        } else {
+            self.internal_credit(to, delivered);
+            self.record_account_operation(from, account_history::OpType::Send, Some(to), delivered);
+            self.record_account_operation(
+                to,
+                account_history::OpType::Receive,
+                Some(from),
+                delivered,
+            );
+        }
+        self.record_transfer(from, to, delivered);
+        self.notify_transfer_observers(from, to, delivered);
+        if fee > 0 {
+            self.total_supply -= fee;
+            self.total_burned += fee;
+            self.record_account_operation(from, account_history::OpType::Burn, None, fee);
+            events::FtBurn {
+                owner_id: from,
+                amount: fee.into(),
+            }
+            .emit(self);
+        }
+    }
+
+    fn internal_spend_allowance(&mut self, owner: &AccountId, spender: &AccountId, value: u128) {
+        require!(
+            !self.allowance_expiry_passed(owner, spender),
+            "insufficient allowance"
+        );
+        let allowance = self
+            .allowed
+            .get_mut(owner)
+            .and_then(|allowed| allowed.get_mut(spender));
+        require!(
+            allowance
+                .as_deref()
+                .is_some_and(|allowance| *allowance >= value),
+            "insufficient allowance"
+        );
+        let allowance = allowance.unwrap();
+        let old_allowance = *allowance;
+        *allowance -= value;
+        self.record_allowance_change(owner, spender, old_allowance - value);
+        events::Approval {
+            owner,
+            spender,
+            old_allowance: old_allowance.into(),
+            new_allowance: (old_allowance - value).into(),
+        }
+        .emit(self);
+    }
+
+    pub fn approve(&mut self, spender: AccountId, value: U128) {
+        self.assert_not_migrating();
+        self.internal_approve(&predecessor_account_id(), spender, value.into());
+    }
+
+    /// Approves several spenders at once; they share the owner's inner map.
+    pub fn approve_many(&mut self, approvals: Vec<(AccountId, U128)>) {
+        self.assert_not_migrating();
+        self.assert_batch_size(approvals.len());
+        let owner = predecessor_account_id();
+        for (spender, value) in approvals {
+            self.internal_approve(&owner, spender, value.into());
+        }
+    }
+
+    pub fn increase_allowance(&mut self, spender: AccountId, added_value: U128) -> U128 {
+        self.assert_not_migrating();
+        let owner = predecessor_account_id();
+        let current = *self.allowance(owner.clone(), spender.clone());
+        let value = current
+            .checked_add(added_value.into())
+            .expect("allowance overflow");
+        self.internal_approve(&owner, spender, value);
+        value.into()
+    }
+
+    pub fn decrease_allowance(&mut self, spender: AccountId, subtracted_value: U128) -> U128 {
+        self.assert_not_migrating();
+        let owner = predecessor_account_id();
+        let current = *self.allowance(owner.clone(), spender.clone());
+        let value = current
+            .checked_sub(subtracted_value.into())
+            .expect("allowance below zero");
+        self.internal_approve(&owner, spender, value);
+        value.into()
+    }
+
+    pub fn set_max_allowance(&mut self, max_allowance: Option<U128>) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.max_allowance = max_allowance.map(|max| max.0);
+    }
+
+    /// Caps how many distinct spenders one owner may approve; changing an
+    /// existing spender's allowance is always allowed.
+    pub fn set_max_spenders_per_owner(&mut self, max_spenders: Option<u32>) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.max_spenders_per_owner = max_spenders;
+    }
+
+    fn assert_allowance_limits(&self, owner: &AccountId, spender: &AccountId, value: u128) {
+        if let Some(max_allowance) = self.max_allowance {
+            require!(value <= max_allowance, "allowance exceeds cap");
+        }
+        if let Some(max_spenders) = self.max_spenders_per_owner {
+            require!(
+                self.has_spender(owner, spender) || self.spender_count(owner) < max_spenders,
+                "too many spenders"
+            );
+        }
+    }
+
+    fn has_spender(&self, owner: &AccountId, spender: &AccountId) -> bool {
+        self.allowed
+            .get(owner)
+            .is_some_and(|allowed| allowed.contains_key(spender))
+            || self
+                .periodic_allowances
+                .get(owner)
+                .is_some_and(|allowances| allowances.contains_key(spender))
+    }
+
+    /// Distinct spenders holding a plain or periodic allowance from `owner`.
+    fn spender_count(&self, owner: &AccountId) -> u32 {
+        let plain = self.allowed.get(owner);
+        let periodic_only = self.periodic_allowances.get(owner).map_or(0, |allowances| {
+            allowances
+                .keys()
+                .filter(|spender| !plain.is_some_and(|allowed| allowed.contains_key(*spender)))
+                .count() as u32
+        });
+        plain.map_or(0, |allowed| allowed.len()) + periodic_only
+    }
+
+    fn internal_approve(&mut self, owner: &AccountId, spender: AccountId, value: u128) {
+        self.assert_allowance_limits(owner, &spender, value);
+
+        let allowance_exist = self.allowed.contains_key(owner);
+        if !allowance_exist {
+            let prefix = [
+                nested_key(&self.storage_prefix, StorageKey::AllowedByOwner),
+                env::sha256(owner.as_bytes()),
+            ]
+            .concat();
+            self.allowed
+                .insert(owner.clone(), UnorderedMap::new(prefix));
+            self.internal_index_account(owner);
+        }
+
+        self.allowance_expiry
+            .remove(&(owner.clone(), spender.clone()));
+        self.record_allowance_change(owner, &spender, value);
+        self.allowed.get_mut(owner).unwrap().insert(spender, value);
+    }
+
+    pub fn allowance(&self, owner: AccountId, spender: AccountId) -> &u128 {
+        if self.allowance_expiry_passed(&owner, &spender) {
+            return &0;
+        }
+        self.allowed
+            .get(&owner)
+            .and_then(|allowed| allowed.get(&spender))
+            .unwrap_or(&0u128)
+    }
+
+    pub fn get_allowance(&self, owner: AccountId, spender: AccountId) -> U128 {
+        (*self.allowance(owner, spender)).into()
+    }
+
+    /// Sum of every allowance granted by `owner`, saturating at `u128::MAX`.
+    pub fn total_approved(&self, owner: AccountId) -> U128 {
+        self.allowed
+            .get(&owner)
+            .map(|allowed| {
+                allowed
+                    .values()
+                    .fold(0u128, |total, allowance| total.saturating_add(*allowance))
+            })
+            .unwrap_or(0)
+            .into()
+    }
+
+    /// Drops every plain and periodic allowance granted by the caller.
+    pub fn revoke_all(&mut self) {
+        self.assert_not_migrating();
+        let owner_id = predecessor_account_id();
+        let mut spender_ids = Vec::new();
+        if let Some(mut allowed) = self.allowed.remove(&owner_id) {
+            spender_ids.extend(allowed.keys().cloned());
+            allowed.clear();
+            for spender_id in &spender_ids {
+                self.allowance_expiry
+                    .remove(&(owner_id.clone(), spender_id.clone()));
+            }
+        }
+        if let Some(mut allowances) = self.periodic_allowances.remove(&owner_id) {
+            for spender_id in allowances.keys() {
+                if !spender_ids.contains(spender_id) {
+                    spender_ids.push(spender_id.clone());
+                }
+            }
+            allowances.clear();
+        }
+        if !spender_ids.is_empty() {
+            events::AllowancesRevoked {
+                owner_id: &owner_id,
+                spender_ids: &spender_ids,
+            }
+            .emit(self);
+        }
+    }
+
+    /// Returns the pending mint id instead of minting while
+    /// `mint_requires_confirmation` is set.
+    pub fn mint(&mut self, to: AccountId, value: U128) -> Option<u64> {
+        self.assert_not_migrating();
+        self.assert_minter();
+        self.assert_below_mint_threshold(value.0);
+        if self.mint_requires_confirmation {
+            return Some(self.internal_queue_mint(to, value));
+        }
+        self.internal_mint(to, value);
+        None
+    }
+
+    pub fn mint_batch(&mut self, accounts: Vec<(AccountId, U128)>) {
+        self.assert_not_migrating();
+        self.assert_minter();
+        self.assert_direct_mint();
+        self.assert_batch_size(accounts.len());
+        for (account_id, amount) in &accounts {
+            self.assert_below_mint_threshold(amount.0);
+            self.internal_mint_unlogged(account_id.clone(), *amount);
+        }
+        let events: Vec<events::FtMint> = accounts
+            .iter()
+            .map(|(owner_id, amount)| events::FtMint {
+                owner_id,
+                amount: *amount,
+            })
+            .collect();
+        self.emit_batch(&events, events::FtMint::emit_many);
+    }
+
+    pub(crate) fn internal_mint(&mut self, to: AccountId, value: U128) {
+        self.internal_mint_unlogged(to.clone(), value);
+        events::FtMint {
+            owner_id: &to,
+            amount: value,
+        }
+        .emit(self);
+    }
+
+    fn internal_mint_unlogged(&mut self, to: AccountId, value: U128) {
+        require!(!self.minting_paused, "minting is paused");
+        if let Some(max_supply) = self.max_supply {
+            require!(
+                self.total_supply + value.0 <= max_supply,
+                "max supply exceeded"
+            );
+        }
+        self.total_supply += value.0;
+        self.record_account_operation(&to, account_history::OpType::Mint, None, value.0);
+        self.record_clawback_mint(&to, value.0);
+        self.internal_credit(&to, value.0);
+    }
+
+    /// Mints to `to` and notifies it via `ft_on_mint`. The embedding
+    /// contract must expose `resolve_mint` as a `#[private]` callback.
+    pub fn mint_call(&mut self, to: AccountId, value: U128, msg: String) -> Promise {
+        self.assert_not_migrating();
+        self.assert_minter();
+        self.assert_direct_mint();
+        self.assert_below_mint_threshold(value.0);
+        self.internal_mint(to.clone(), value);
+        ext_mint_receiver::ext(to.clone())
+            .with_static_gas(GAS_FOR_FT_ON_MINT)
+            .ft_on_mint(value, msg)
+            .then(
+                ext_mint_resolver::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_MINT)
+                    .resolve_mint(to, value),
+            )
+    }
+
+    /// Burns back whatever part of a `mint_call` the receiver rejected, up
+    /// to its unlocked balance, and returns the amount that stayed minted.
+    pub fn resolve_mint(&mut self, receiver_id: AccountId, amount: U128) -> U128 {
+        let unused = match env::promise_result(0) {
+            PromiseResult::Successful(value) => serde_json::from_slice::<U128>(&value)
+                .map(|unused| unused.0.min(amount.0))
+                .unwrap_or(amount.0),
+            _ => amount.0,
+        };
+        let balance = *self.balance_of(receiver_id.clone()).unwrap_or(&0u128);
+        let unlocked = balance.saturating_sub(self.locked_of(receiver_id.clone()).0);
+        let refund = unused.min(unlocked);
+        if refund > 0 {
+            self.internal_burn(&receiver_id, refund);
+        }
+        (amount.0 - refund).into()
+    }
+
+    pub fn set_max_supply(&mut self, max_supply: Option<U128>) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.max_supply = max_supply.map(|max| max.0);
+    }
+
+    pub fn configure_faucet(&mut self, enabled: bool, amount: U128, cooldown_secs: u64) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.faucet_enabled = enabled;
+        self.faucet_amount = amount.0;
+        self.faucet_cooldown_secs = cooldown_secs;
+    }
+
+    /// Testnet onboarding: mints `faucet_amount` to the caller at most once
+    /// per `faucet_cooldown_secs`.
+    pub fn faucet_claim(&mut self) -> U128 {
+        self.assert_not_migrating();
+        require!(self.faucet_enabled, "faucet disabled");
+        let account_id = predecessor_account_id();
+        let now = env::block_timestamp();
+        if let Some(last_claim_ts) = self.faucet_claims.get(&account_id) {
+            require!(
+                now >= last_claim_ts + self.faucet_cooldown_secs * NANOS_PER_SEC,
+                "faucet cooldown active"
+            );
+        }
+        self.faucet_claims.insert(account_id.clone(), now);
+        self.internal_mint(account_id, self.faucet_amount.into());
+        self.faucet_amount.into()
+    }
+
+    pub fn burn(&mut self, account_id: AccountId, value: U128) {
+        self.assert_not_migrating();
+        let caller = predecessor_account_id();
+        require!(
+            caller == account_id || caller == self.owner_id,
+            "only account or owner"
+        );
+        self.assert_burnable(&account_id, value.0);
+        self.internal_burn(&account_id, value.0);
+    }
+
+    pub(crate) fn assert_burnable(&self, account_id: &AccountId, amount: u128) {
+        require!(amount != 0, "burn amount must be positive");
+        let balance = *self.balance_of(account_id.clone()).unwrap_or(&0u128);
+        require!(balance >= amount, "insufficient balance");
+        require!(
+            balance - amount >= self.locked_of(account_id.clone()).0,
+            "burn exceeds unlocked balance"
+        );
+    }
+
+    /// Burn bookkeeping shared by every burn entrypoint; the caller has
+    /// checked that `amount` is unlocked balance of `account_id`.
+    pub(crate) fn internal_burn(&mut self, account_id: &AccountId, amount: u128) {
+        self.internal_burn_unlogged(account_id, amount);
+        events::FtBurn {
+            owner_id: account_id,
+            amount: amount.into(),
+        }
+        .emit(self);
+    }
+
+    fn internal_burn_unlogged(&mut self, account_id: &AccountId, amount: u128) {
+        self.assert_burn_above_min_supply(amount);
+        let balance = self.balance[account_id].amount;
+        self.internal_set_balance(account_id, balance - amount);
+        self.total_supply -= amount;
+        self.total_burned += amount;
+        self.record_account_operation(account_id, account_history::OpType::Burn, None, amount);
+    }
+
+    pub fn burn_all(&mut self, account_id: AccountId) -> U128 {
+        self.assert_not_migrating();
+        let caller = predecessor_account_id();
+        require!(
+            caller == account_id || caller == self.owner_id,
+            "only account or owner"
+        );
+        let locked = self.locked_of(account_id.clone()).0;
+        let balance = self.balance.get(&account_id).map(|entry| entry.amount);
+        require!(balance.is_some() || locked == 0, "no balance");
+        let amount = balance.unwrap_or(0).saturating_sub(locked);
+        if amount > 0 {
+            self.internal_burn(&account_id, amount);
+        }
+        if locked == 0 {
+            self.internal_remove_balance(&account_id);
+        }
+        amount.into()
+    }
+
+    /// Validates every burn before applying any. Repeated accounts are
+    /// checked against their combined amount.
+    pub fn batch_burn(&mut self, accounts: Vec<(AccountId, U128)>) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.assert_batch_size(accounts.len());
+        let mut per_account: HashMap<&AccountId, u128> = HashMap::new();
+        for (account_id, amount) in &accounts {
+            let burned = per_account.entry(account_id).or_default();
+            *burned = burned.checked_add(amount.0).expect("amount overflow");
+        }
+        for (account_id, burned) in &per_account {
+            let balance = *self.balance_of((*account_id).clone()).unwrap_or(&0u128);
+            require!(balance >= *burned, "insufficient balance");
+            require!(
+                balance - burned >= self.locked_of((*account_id).clone()).0,
+                "burn exceeds unlocked balance"
+            );
+        }
+
+        let total: u128 = per_account.values().sum();
+        self.assert_burn_above_min_supply(total);
+        for (account_id, amount) in &accounts {
+            self.internal_burn_unlogged(account_id, amount.0);
+        }
+        let events: Vec<events::FtBurn> = accounts
+            .iter()
+            .map(|(owner_id, amount)| events::FtBurn {
+                owner_id,
+                amount: *amount,
+            })
+            .collect();
+        events::FtBurn::emit_many(&events, self);
+    }
+
+    /// Records a pending purchase paid for with the attached NEAR. The
+    /// embedding contract must expose this method as `#[payable]`.
+    pub fn reserve(&mut self, amount: U128) {
+        self.assert_not_migrating();
+        let deposit = env::attached_deposit();
+        require!(deposit > 0, "reservation requires attached deposit");
+        require!(amount.0 > 0, "reservation amount must be positive");
+        let buyer = predecessor_account_id();
+        self.total_reservation_deposits += deposit;
+        match self.reservations.get_mut(&buyer) {
+            Some(reservation) => {
+                reservation.amount += amount.0;
+                reservation.deposit += deposit;
+            }
+            None => {
+                self.reservations.insert(
+                    buyer.clone(),
+                    Reservation {
+                        amount: amount.0,
+                        deposit,
+                    },
+                );
+                self.internal_index_account(&buyer);
+            }
+        }
+    }
+
+    pub fn reservation_of(&self, buyer: AccountId) -> Option<&Reservation> {
+        self.reservations.get(&buyer)
+    }
+
+    pub fn finalize_reservation(&mut self, buyer: AccountId) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        let reservation = self
+            .reservations
+            .remove(&buyer)
+            .expect("no reservation for buyer");
+        self.total_reservation_deposits -= reservation.deposit;
+        self.internal_mint(buyer, reservation.amount.into());
+    }
+
+    pub fn cancel_reservation(&mut self, buyer: AccountId) -> Promise {
+        self.assert_not_migrating();
+        let caller = predecessor_account_id();
+        require!(
+            caller == buyer || caller == self.owner_id,
+            "only buyer or owner"
+        );
+        let reservation = self
+            .reservations
+            .remove(&buyer)
+            .expect("no reservation for buyer");
+        self.total_reservation_deposits -= reservation.deposit;
+        Promise::new(buyer).transfer(reservation.deposit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use near_sdk::{
+        mock::VmAction,
+        test_utils::{get_created_receipts, get_logs, VMContextBuilder},
+        testing_env, PromiseResult, RuntimeFeesConfig, VMConfig, ONE_NEAR,
+    };
+    use std::{cell::Cell, rc::Rc};
+
+    const DECIMALS: u8 = 18;
+    const TOTAL_SUPPLY: u128 = 10u128.pow(9);
+    const DAY_SECS: u64 = 24 * 60 * 60;
+
+    fn get_context(predecessor: String) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder.predecessor_account_id(predecessor.parse().unwrap());
+        builder
+    }
+
+    fn init_contract() -> ERC20 {
+        init_contract_with_supply(TOTAL_SUPPLY)
+    }
+
+    fn init_contract_with_supply(total_supply: u128) -> ERC20 {
+        ERC20::init(
+            "nutinaguti.testnet".parse().unwrap(),
+            "FUN COIN".to_string(),
+            "FUNC".to_string(),
+            DECIMALS,
+            total_supply.into(),
+            events::DEFAULT_EVENT_STANDARD.to_string(),
+            events::DEFAULT_EVENT_VERSION.to_string(),
+            Vec::new(),
+            false,
+            b"t",
+        )
+    }
+
+    #[test]
+    fn test_approve() {
+        let predecessor = "nutinaguti.testnet".parse().unwrap();
+        let context = get_context(predecessor);
+        testing_env!(context.build());
+
+        let mut contract = init_contract();
+        contract.approve("test.testnet".parse().unwrap(), 1.into());
+        let allowance = contract.allowance(
+            "nutinaguti.testnet".parse().unwrap(),
+            "test.testnet".parse().unwrap(),
+        );
+        assert_eq!(1, *allowance);
+
+        contract.approve("test.testnet".parse().unwrap(), 2.into());
+        let allowance = contract.allowance(
+            "nutinaguti.testnet".parse().unwrap(),
+            "test.testnet".parse().unwrap(),
+        );
+        assert_eq!(2, *allowance);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_transfer_negative() {
+        let predecessor = "nutinaguti.testnet".parse().unwrap();
+        let context = get_context(predecessor);
+        testing_env!(context.build());
+
+        let mut contract = init_contract();
+        contract.transfer("test.testnet".parse().unwrap(), 1.into());
+    }
+
+    #[test]
+    fn test_transfer_positive() {
+        let predecessor = "nutinaguti.testnet".parse().unwrap();
+        let context = get_context(predecessor);
+        testing_env!(context.build());
+
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 1.into());
+        contract.transfer("test.testnet".parse().unwrap(), 1.into());
+        assert_eq!(
+            0u128,
+            *contract
+                .balance_of("nutinaguti.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            1u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_transfer_from_negative() {
+        let predecessor = "nutinaguti.testnet".parse().unwrap();
+        let context = get_context(predecessor);
+        testing_env!(context.build());
+
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 1.into());
+        contract.transfer_from(
+            "test.testnet".parse().unwrap(),
+            "nutinaguti.testnet".parse().unwrap(),
+            1.into(),
+        );
+    }
+
+    #[test]
+    fn test_transfer_from_positive() {
+        let predecessor = "nutinaguti.testnet".parse().unwrap();
+        let context = get_context(predecessor);
+
+        let predecessor_2 = "test.testnet".parse().unwrap();
+        let context_2 = get_context(predecessor_2);
+        testing_env!(context.build());
+
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 1.into());
+
+        testing_env!(context_2.build());
+        contract.approve("nutinaguti.testnet".parse().unwrap(), 1.into());
+        testing_env!(context.build());
+
+        contract.transfer_from(
+            "test.testnet".parse().unwrap(),
+            "nutinaguti.testnet".parse().unwrap(),
+            1.into(),
+        );
+    }
+
+    #[test]
+    fn test_reserve_and_finalize() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        let context = get_context(owner);
+        let buyer = "test.testnet".parse().unwrap();
+        let mut buyer_context = get_context(buyer);
+        buyer_context.attached_deposit(ONE_NEAR);
+        testing_env!(context.build());
+
+        let mut contract = init_contract();
+
+        testing_env!(buyer_context.build());
+        contract.reserve(10.into());
+        contract.reserve(5.into());
+        let reservation = contract
+            .reservation_of("test.testnet".parse().unwrap())
+            .unwrap();
+        assert_eq!(15, reservation.amount);
+        assert_eq!(2 * ONE_NEAR, reservation.deposit);
+
+        testing_env!(context.build());
+        contract.finalize_reservation("test.testnet".parse().unwrap());
+        assert!(contract
+            .reservation_of("test.testnet".parse().unwrap())
+            .is_none());
+        assert_eq!(
+            15u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "only owner")]
+    fn test_finalize_reservation_not_owner() {
+        let buyer = "test.testnet".parse().unwrap();
+        let mut context = get_context(buyer);
+        context.attached_deposit(ONE_NEAR);
+        testing_env!(context.build());
+
+        let mut contract = init_contract();
+        contract.reserve(10.into());
+        contract.finalize_reservation("test.testnet".parse().unwrap());
+    }
+
+    #[test]
+    fn test_cancel_reservation() {
+        let buyer = "test.testnet".parse().unwrap();
+        let mut context = get_context(buyer);
+        context.attached_deposit(ONE_NEAR);
+        testing_env!(context.build());
+
+        let mut contract = init_contract();
+        contract.reserve(10.into());
+        drop(contract.cancel_reservation("test.testnet".parse().unwrap()));
+
+        assert!(contract
+            .reservation_of("test.testnet".parse().unwrap())
+            .is_none());
+        assert!(contract
+            .balance_of("test.testnet".parse().unwrap())
+            .is_none());
+        let receipts = get_created_receipts();
+        assert_eq!(1, receipts.len());
+        assert_eq!("test.testnet", receipts[0].receiver_id.as_str());
+        assert_eq!(
+            vec![VmAction::Transfer { deposit: ONE_NEAR }],
+            receipts[0].actions
+        );
+    }
+
+    fn setup_pull_payment() -> ERC20 {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.set_pull_interval(DAY_SECS);
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.approve("test.testnet".parse().unwrap(), 10.into());
+        contract
+    }
+
+    #[test]
+    fn test_pull_payment() {
+        let mut contract = setup_pull_payment();
+        let spender = "test.testnet".parse().unwrap();
+        let mut context = get_context(spender);
+        testing_env!(context.build());
+        contract.pull_payment("nutinaguti.testnet".parse().unwrap(), 4.into());
+
+        testing_env!(context.block_timestamp(DAY_SECS * 1_000_000_000).build());
+        contract.pull_payment("nutinaguti.testnet".parse().unwrap(), 4.into());
+
+        assert_eq!(
+            2,
+            *contract.allowance(
+                "nutinaguti.testnet".parse().unwrap(),
+                "test.testnet".parse().unwrap(),
+            )
+        );
+        assert_eq!(
+            8u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_pull_payment_exceeds_allowance() {
+        let mut contract = setup_pull_payment();
+        let spender = "test.testnet".parse().unwrap();
+        let mut context = get_context(spender);
+        testing_env!(context.build());
+        contract.pull_payment("nutinaguti.testnet".parse().unwrap(), 4.into());
+        testing_env!(context.block_timestamp(DAY_SECS * 1_000_000_000).build());
+        contract.pull_payment("nutinaguti.testnet".parse().unwrap(), 4.into());
+        testing_env!(context
+            .block_timestamp(2 * DAY_SECS * 1_000_000_000)
+            .build());
+        contract.pull_payment("nutinaguti.testnet".parse().unwrap(), 4.into());
+    }
+
+    #[test]
+    #[should_panic(expected = "pull interval not elapsed")]
+    fn test_pull_payment_within_interval() {
+        let mut contract = setup_pull_payment();
+        let spender = "test.testnet".parse().unwrap();
+        testing_env!(get_context(spender).build());
+        contract.pull_payment("nutinaguti.testnet".parse().unwrap(), 4.into());
+        contract.pull_payment("nutinaguti.testnet".parse().unwrap(), 4.into());
+    }
+
+    #[test]
+    fn test_upgrade_history() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        let mut context = get_context(owner);
+        testing_env!(context.block_timestamp(100).build());
+        let mut contract = init_contract();
+        contract.migrate(2);
+        testing_env!(context.block_timestamp(200).build());
+        contract.migrate(3);
+
+        assert_eq!(3, contract.version);
+        assert_eq!(
+            vec![
+                UpgradeRecord {
+                    old_version: 1,
+                    new_version: 2,
+                    timestamp: 100,
+                },
+                UpgradeRecord {
+                    old_version: 2,
+                    new_version: 3,
+                    timestamp: 200,
+                },
+            ],
+            contract.upgrade_history()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "version must increase")]
+    fn test_migrate_same_version() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.migrate(1);
+    }
+
+    #[test]
+    fn test_percent_owned_zero_supply() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let contract = init_contract_with_supply(0);
+        assert_eq!(0, contract.percent_owned("test.testnet".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_percent_owned() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract_with_supply(0);
+        contract.mint("test.testnet".parse().unwrap(), 25.into());
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 75.into());
+        assert_eq!(
+            2500,
+            contract.percent_owned("test.testnet".parse().unwrap())
+        );
+        assert_eq!(0, contract.percent_owned("nobody.testnet".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_set_name_and_symbol() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.set_name("FUNNIER COIN".to_string());
+        contract.set_symbol("FUNR".to_string());
+
+        assert_eq!("FUNNIER COIN", contract.name());
+        assert_eq!("FUNR", contract.symbol());
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"name":"FUNNIER COIN","symbol":"FUNC"}],"event":"metadata_update","standard":"nep141","version":"1.0.0"}"#,
+                r#"EVENT_JSON:{"data":[{"name":"FUNNIER COIN","symbol":"FUNR"}],"event":"metadata_update","standard":"nep141","version":"1.0.0"}"#,
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "name must not be empty")]
+    fn test_set_name_empty() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.set_name("".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "symbol must not be empty")]
+    fn test_set_symbol_empty() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.set_symbol("".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "only owner")]
+    fn test_set_name_not_owner() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        testing_env!(get_context("test.testnet".parse().unwrap()).build());
+        contract.set_name("STOLEN".to_string());
+    }
+
+    #[test]
+    fn test_burn_all() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 40.into());
+        assert_eq!(TOTAL_SUPPLY + 40, *contract.total_supply());
+
+        testing_env!(get_context("test.testnet".parse().unwrap()).build());
+        let burned = contract.burn_all("test.testnet".parse().unwrap());
+
+        assert_eq!(40, burned.0);
+        assert!(contract
+            .balance_of("test.testnet".parse().unwrap())
+            .is_none());
+        assert_eq!(TOTAL_SUPPLY, *contract.total_supply());
+    }
+
+    #[test]
+    #[should_panic(expected = "only account or owner")]
+    fn test_burn_all_unauthorized() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 40.into());
+
+        testing_env!(get_context("other.testnet".parse().unwrap()).build());
+        contract.burn_all("test.testnet".parse().unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "contract is migrating")]
+    fn test_transfer_during_migration() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 2.into());
+        contract.begin_migration();
+        contract.transfer("test.testnet".parse().unwrap(), 1.into());
+    }
+
+    #[test]
+    fn test_transfer_after_migration() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 2.into());
+        contract.begin_migration();
+        contract.migrate(2);
+
+        assert!(!contract.migrating);
+        contract.transfer("test.testnet".parse().unwrap(), 1.into());
+    }
+
+    #[test]
+    #[should_panic(expected = "insufficient balance")]
+    fn test_transfer_from_insufficient_balance() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 1.into());
+        contract.approve("test.testnet".parse().unwrap(), 5.into());
+
+        testing_env!(get_context("test.testnet".parse().unwrap()).build());
+        contract.transfer_from(
+            "nutinaguti.testnet".parse().unwrap(),
+            "test.testnet".parse().unwrap(),
+            2.into(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "insufficient allowance")]
+    fn test_transfer_from_insufficient_allowance() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 5.into());
+        contract.approve("test.testnet".parse().unwrap(), 1.into());
+
+        testing_env!(get_context("test.testnet".parse().unwrap()).build());
+        contract.transfer_from(
+            "nutinaguti.testnet".parse().unwrap(),
+            "test.testnet".parse().unwrap(),
+            2.into(),
+        );
+    }
+
+    fn resolve_mint_with(result: PromiseResult, contract: &mut ERC20) -> U128 {
+        let context = get_context("erc20.testnet".parse().unwrap());
+        testing_env!(
+            context.build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+        contract.resolve_mint("receiver.testnet".parse().unwrap(), 10.into())
+    }
+
+    #[test]
+    fn test_mint_call_schedules_receiver() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        drop(contract.mint_call(
+            "receiver.testnet".parse().unwrap(),
+            10.into(),
+            "hello".to_string(),
+        ));
+
+        assert_eq!(
+            10u128,
+            *contract
+                .balance_of("receiver.testnet".parse().unwrap())
+                .unwrap()
+        );
+        let receipts = get_created_receipts();
+        assert_eq!("receiver.testnet", receipts[0].receiver_id.as_str());
+        assert!(matches!(
+            &receipts[0].actions[0],
+            VmAction::FunctionCall { function_name, .. } if function_name == "ft_on_mint"
+        ));
+        assert!(matches!(
+            &receipts[1].actions[0],
+            VmAction::FunctionCall { function_name, .. } if function_name == "resolve_mint"
+        ));
+    }
+
+    #[test]
+    fn test_resolve_mint_accepted() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("receiver.testnet".parse().unwrap(), 10.into());
+
+        let accepted =
+            resolve_mint_with(PromiseResult::Successful(b"\"0\"".to_vec()), &mut contract);
+        assert_eq!(10, accepted.0);
+        assert_eq!(
+            10u128,
+            *contract
+                .balance_of("receiver.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(TOTAL_SUPPLY + 10, *contract.total_supply());
+    }
+
+    #[test]
+    fn test_resolve_mint_rejected() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("receiver.testnet".parse().unwrap(), 10.into());
+
+        let accepted =
+            resolve_mint_with(PromiseResult::Successful(b"\"10\"".to_vec()), &mut contract);
+        assert_eq!(0, accepted.0);
+        assert_eq!(
+            0u128,
+            *contract
+                .balance_of("receiver.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(TOTAL_SUPPLY, *contract.total_supply());
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"10","owner_id":"receiver.testnet"}],"event":"ft_burn","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    fn test_resolve_mint_keeps_locked_balance() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("receiver.testnet".parse().unwrap(), 10.into());
+        contract.set_locked("receiver.testnet".parse().unwrap(), 4.into());
+
+        let accepted =
+            resolve_mint_with(PromiseResult::Successful(b"\"10\"".to_vec()), &mut contract);
+        assert_eq!(4, accepted.0);
+        assert_eq!(
+            4u128,
+            *contract
+                .balance_of("receiver.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_resolve_mint_receiver_failed() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("receiver.testnet".parse().unwrap(), 10.into());
+
+        let accepted = resolve_mint_with(PromiseResult::Failed, &mut contract);
+        assert_eq!(0, accepted.0);
+        assert_eq!(TOTAL_SUPPLY, *contract.total_supply());
+    }
+
+    fn setup_allowance_cap() -> ERC20 {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.set_max_allowance(Some(1000.into()));
+        contract
+    }
+
+    #[test]
+    fn test_approve_at_cap() {
+        let mut contract = setup_allowance_cap();
+        contract.approve("test.testnet".parse().unwrap(), 1000.into());
+        assert_eq!(
+            1000,
+            *contract.allowance(
+                "nutinaguti.testnet".parse().unwrap(),
+                "test.testnet".parse().unwrap(),
+            )
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "allowance exceeds cap")]
+    fn test_approve_above_cap() {
+        let mut contract = setup_allowance_cap();
+        contract.approve("test.testnet".parse().unwrap(), 1001.into());
+    }
+
+    fn setup_spender_cap() -> ERC20 {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.set_max_spenders_per_owner(Some(2));
+        contract.approve("a.testnet".parse().unwrap(), 1.into());
+        contract.approve("b.testnet".parse().unwrap(), 1.into());
+        contract
+    }
+
+    #[test]
+    fn test_approve_at_spender_cap() {
+        let mut contract = setup_spender_cap();
+        contract.approve("b.testnet".parse().unwrap(), 5.into());
+        assert_eq!(
+            5,
+            *contract.allowance(
+                "nutinaguti.testnet".parse().unwrap(),
+                "b.testnet".parse().unwrap()
+            )
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "too many spenders")]
+    fn test_approve_above_spender_cap() {
+        let mut contract = setup_spender_cap();
+        contract.approve("c.testnet".parse().unwrap(), 1.into());
+    }
+
+    #[test]
+    fn test_increase_allowance_at_cap() {
+        let mut contract = setup_allowance_cap();
+        contract.approve("test.testnet".parse().unwrap(), 400.into());
+        let allowance = contract.increase_allowance("test.testnet".parse().unwrap(), 600.into());
+        assert_eq!(1000, allowance.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "allowance exceeds cap")]
+    fn test_increase_allowance_above_cap() {
+        let mut contract = setup_allowance_cap();
+        contract.approve("test.testnet".parse().unwrap(), 400.into());
+        contract.increase_allowance("test.testnet".parse().unwrap(), 601.into());
+    }
+
+    #[test]
+    fn test_storage_balance_bounds() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let contract = init_contract();
+        let bounds = contract.storage_balance_bounds();
+
+        assert!(contract.account_storage_usage > 0);
+        let account_cost = contract.account_storage_usage as u128 * env::storage_byte_cost();
+        assert_eq!(account_cost, bounds.min.0);
+        assert_eq!(Some(bounds.min), bounds.max);
+    }
+
+    #[test]
+    fn test_spendable_balance() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.mint("test.testnet".parse().unwrap(), 100.into());
+        contract.mint("other.testnet".parse().unwrap(), 100.into());
+
+        contract.freeze("test.testnet".parse().unwrap());
+        contract.set_locked("other.testnet".parse().unwrap(), 30.into());
+
+        assert_eq!(
+            100,
+            contract
+                .spendable_balance("nutinaguti.testnet".parse().unwrap())
+                .0
+        );
+        assert_eq!(
+            0,
+            contract
+                .spendable_balance("test.testnet".parse().unwrap())
+                .0
+        );
+        assert_eq!(
+            70,
+            contract
+                .spendable_balance("other.testnet".parse().unwrap())
+                .0
+        );
+
+        contract.pause();
+        assert_eq!(
+            0,
+            contract
+                .spendable_balance("nutinaguti.testnet".parse().unwrap())
+                .0
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "balance is locked")]
+    fn test_transfer_locked_balance() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.set_locked("nutinaguti.testnet".parse().unwrap(), 30.into());
+        contract.transfer("test.testnet".parse().unwrap(), 71.into());
+    }
+
+    #[test]
+    #[should_panic(expected = "account is frozen")]
+    fn test_transfer_frozen() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.freeze("nutinaguti.testnet".parse().unwrap());
+        contract.transfer("test.testnet".parse().unwrap(), 1.into());
+    }
+
+    #[test]
+    #[should_panic(expected = "contract is paused")]
+    fn test_transfer_paused() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.pause();
+        contract.transfer("test.testnet".parse().unwrap(), 1.into());
+    }
+
+    #[test]
+    fn test_revoke_all() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        let spenders: Vec<AccountId> = ["a.testnet", "b.testnet", "c.testnet"]
+            .iter()
+            .map(|spender| spender.parse().unwrap())
+            .collect();
+        for spender in &spenders {
+            contract.approve(spender.clone(), 10.into());
+        }
+
+        contract.revoke_all();
+
+        for spender in spenders {
+            assert_eq!(
+                0,
+                *contract.allowance("nutinaguti.testnet".parse().unwrap(), spender)
+            );
+        }
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"owner_id":"nutinaguti.testnet","spender_ids":["a.testnet","b.testnet","c.testnet"]}],"event":"allowances_revoked","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    fn test_split_transfer() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.split_transfer(
+            vec![
+                ("a.testnet".parse().unwrap(), 7000),
+                ("b.testnet".parse().unwrap(), 3000),
+            ],
+            100.into(),
+        );
+
+        assert_eq!(
+            70u128,
+            *contract.balance_of("a.testnet".parse().unwrap()).unwrap()
+        );
+        assert_eq!(
+            30u128,
+            *contract.balance_of("b.testnet".parse().unwrap()).unwrap()
+        );
+        assert_eq!(
+            0u128,
+            *contract
+                .balance_of("nutinaguti.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_split_transfer_remainder() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        contract.split_transfer(
+            vec![
+                ("a.testnet".parse().unwrap(), 3333),
+                ("b.testnet".parse().unwrap(), 3333),
+                ("c.testnet".parse().unwrap(), 3334),
+            ],
+            10.into(),
+        );
+
+        assert_eq!(
+            3u128,
+            *contract.balance_of("a.testnet".parse().unwrap()).unwrap()
+        );
+        assert_eq!(
+            3u128,
+            *contract.balance_of("b.testnet".parse().unwrap()).unwrap()
+        );
+        assert_eq!(
+            4u128,
+            *contract.balance_of("c.testnet".parse().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "weights must sum to 10000")]
+    fn test_split_transfer_bad_weights() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.split_transfer(
+            vec![
+                ("a.testnet".parse().unwrap(), 7000),
+                ("b.testnet".parse().unwrap(), 2000),
+            ],
+            100.into(),
+        );
+    }
+
+    #[test]
+    fn test_storage_usage_grows_with_holders() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        let initial_usage = contract.storage_usage();
+
+        for holder in ["a.testnet", "b.testnet", "c.testnet"] {
+            contract.mint(holder.parse().unwrap(), 1.into());
+        }
+        contract.balance.flush();
+
+        assert!(contract.storage_usage() > initial_usage);
+    }
+
+    #[test]
+    fn test_operator_transfer_from() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.set_operator("operator.testnet".parse().unwrap(), true);
+        assert!(contract.is_operator(
+            "nutinaguti.testnet".parse().unwrap(),
+            "operator.testnet".parse().unwrap(),
+        ));
+
+        testing_env!(get_context("operator.testnet".parse().unwrap()).build());
+        contract.transfer_from(
+            "nutinaguti.testnet".parse().unwrap(),
+            "test.testnet".parse().unwrap(),
+            60.into(),
+        );
+
+        assert_eq!(
+            60u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"60","operator_id":"operator.testnet","owner_id":"nutinaguti.testnet","receiver_id":"test.testnet"}],"event":"operator_transfer","standard":"nep141","version":"1.0.0"}"#,
+                r#"EVENT_JSON:{"data":[{"amount":"60","new_owner_id":"test.testnet","old_owner_id":"nutinaguti.testnet"}],"event":"ft_transfer","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "insufficient allowance")]
+    fn test_revoked_operator_transfer_from() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.set_operator("operator.testnet".parse().unwrap(), true);
+        contract.set_operator("operator.testnet".parse().unwrap(), false);
+
+        testing_env!(get_context("operator.testnet".parse().unwrap()).build());
+        contract.transfer_from(
+            "nutinaguti.testnet".parse().unwrap(),
+            "test.testnet".parse().unwrap(),
+            60.into(),
+        );
+    }
+
+    fn setup_transfer_cooldown() -> ERC20 {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.set_transfer_cooldown(60);
+        contract
+    }
+
+    #[test]
+    #[should_panic(expected = "transfer cooldown active")]
+    fn test_transfer_within_cooldown() {
+        let mut contract = setup_transfer_cooldown();
+        contract.transfer("test.testnet".parse().unwrap(), 1.into());
+        contract.transfer("test.testnet".parse().unwrap(), 1.into());
+    }
+
+    #[test]
+    #[should_panic(expected = "transfer cooldown active")]
+    fn test_cooldown_survives_drained_balance() {
+        let mut contract = setup_transfer_cooldown();
+        contract.set_min_balance(10.into());
+        contract.transfer("test.testnet".parse().unwrap(), 100.into());
+
+        testing_env!(get_context("test.testnet".parse().unwrap()).build());
+        contract.transfer("nutinaguti.testnet".parse().unwrap(), 100.into());
+        testing_env!(get_context("nutinaguti.testnet".parse().unwrap()).build());
+        contract.transfer("test.testnet".parse().unwrap(), 100.into());
+    }
+
+    #[test]
+    fn test_transfer_after_cooldown() {
+        let mut contract = setup_transfer_cooldown();
+        contract.transfer("test.testnet".parse().unwrap(), 1.into());
+
+        let mut context = get_context("nutinaguti.testnet".parse().unwrap());
+        testing_env!(context.block_timestamp(60 * 1_000_000_000).build());
+        contract.transfer("test.testnet".parse().unwrap(), 1.into());
+
+        assert_eq!(
+            2u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_emergency_withdraw_while_paused() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 100.into());
+        contract.pause();
+
+        testing_env!(get_context("test.testnet".parse().unwrap()).build());
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.transfer("safe.testnet".parse().unwrap(), 1.into())
+        }));
+        assert!(result.is_err());
+
+        let withdrawn = contract.emergency_withdraw("safe.testnet".parse().unwrap());
+        assert_eq!(100, withdrawn.0);
+        assert_eq!(
+            100u128,
+            *contract
+                .balance_of("safe.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "account is frozen")]
+    fn test_emergency_withdraw_frozen() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 100.into());
+        contract.pause();
+        contract.freeze("test.testnet".parse().unwrap());
+
+        testing_env!(get_context("test.testnet".parse().unwrap()).build());
+        contract.emergency_withdraw("safe.testnet".parse().unwrap());
+    }
+
+    #[test]
+    fn test_custom_event_standard() {
+        let owner: AccountId = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner.to_string()).build());
+        let mut contract = ERC20::init(
+            owner,
+            "FUN COIN".to_string(),
+            "FUNC".to_string(),
+            DECIMALS,
+            TOTAL_SUPPLY.into(),
+            "funtoken".to_string(),
+            "2.0.0".to_string(),
+            Vec::new(),
+            false,
+            b"t",
+        );
+        contract.set_symbol("FUNR".to_string());
+
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"name":"FUN COIN","symbol":"FUNR"}],"event":"metadata_update","standard":"funtoken","version":"2.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    fn test_total_approved() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.approve("a.testnet".parse().unwrap(), 100.into());
+        contract.approve("b.testnet".parse().unwrap(), 200.into());
+
+        assert_eq!(
+            300,
+            contract
+                .total_approved("nutinaguti.testnet".parse().unwrap())
+                .0
+        );
+        assert_eq!(
+            0,
+            contract.total_approved("test.testnet".parse().unwrap()).0
+        );
+    }
+
+    #[test]
+    fn test_batch_transfer() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.batch_transfer(vec![
+            ("a.testnet".parse().unwrap(), 10.into()),
+            ("b.testnet".parse().unwrap(), 20.into()),
+        ]);
+
+        assert_eq!(
+            10u128,
+            *contract.balance_of("a.testnet".parse().unwrap()).unwrap()
+        );
+        assert_eq!(
+            20u128,
+            *contract.balance_of("b.testnet".parse().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_batch_transfer_frozen_recipient() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.freeze("b.testnet".parse().unwrap());
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.batch_transfer(vec![
+                ("a.testnet".parse().unwrap(), 10.into()),
+                ("b.testnet".parse().unwrap(), 10.into()),
+                ("c.testnet".parse().unwrap(), 10.into()),
+            ])
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(
+            100u128,
+            *contract
+                .balance_of("nutinaguti.testnet".parse().unwrap())
+                .unwrap()
+        );
+        for recipient in ["a.testnet", "b.testnet", "c.testnet"] {
+            assert!(contract.balance_of(recipient.parse().unwrap()).is_none());
+        }
+    }
+
+    fn setup_faucet() -> ERC20 {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.configure_faucet(true, 50.into(), DAY_SECS);
+        contract
+    }
+
+    #[test]
+    fn test_faucet_claim() {
+        let mut contract = setup_faucet();
+        let mut context = get_context("test.testnet".parse().unwrap());
+        testing_env!(context.build());
+        assert_eq!(50, contract.faucet_claim().0);
+
+        testing_env!(context.block_timestamp(DAY_SECS * 1_000_000_000).build());
+        contract.faucet_claim();
+
+        assert_eq!(
+            100u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "faucet cooldown active")]
+    fn test_faucet_claim_twice() {
+        let mut contract = setup_faucet();
+        testing_env!(get_context("test.testnet".parse().unwrap()).build());
+        contract.faucet_claim();
+        contract.faucet_claim();
+    }
+
+    #[test]
+    #[should_panic(expected = "faucet disabled")]
+    fn test_faucet_disabled() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.faucet_claim();
+    }
+
+    #[test]
+    fn test_events_since() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.transfer("a.testnet".parse().unwrap(), 1.into());
+
+        let (records, cursor) = contract.events_since(0);
+        assert_eq!(1, records.len());
+        assert_eq!(1, cursor);
+
+        contract.transfer("b.testnet".parse().unwrap(), 2.into());
+        let (records, cursor) = contract.events_since(cursor);
+        assert_eq!(2, cursor);
+        assert_eq!(
+            vec![transfer_log::TransferRecord {
+                id: 1,
+                from: "nutinaguti.testnet".parse().unwrap(),
+                to: "b.testnet".parse().unwrap(),
+                amount: 2.into(),
+                timestamp: 0,
+            }],
+            records
+        );
+    }
+
+    #[test]
+    fn test_events_since_wraps() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 1000.into());
+        for _ in 0..transfer_log::TRANSFER_LOG_CAPACITY + 5 {
+            // Each transfer logs an event; a fresh context keeps the mocked
+            // receipt under its log limit.
+            testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+            contract.transfer("a.testnet".parse().unwrap(), 1.into());
+        }
+
+        let (records, cursor) = contract.events_since(0);
+        assert_eq!(transfer_log::TRANSFER_LOG_CAPACITY + 5, cursor);
+        assert_eq!(transfer_log::TRANSFER_LOG_CAPACITY as usize, records.len());
+        assert_eq!(5, records[0].id);
+    }
+
+    #[test]
+    #[should_panic(expected = "only pauser")]
+    fn test_owner_cannot_pause_with_separate_pauser() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.set_pauser("pauser.testnet".parse().unwrap());
+        contract.pause();
+    }
+
+    #[test]
+    fn test_pauser_can_pause() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.set_pauser("pauser.testnet".parse().unwrap());
+
+        testing_env!(get_context("pauser.testnet".parse().unwrap()).build());
+        contract.pause();
+        assert!(contract.is_paused());
+        contract.unpause();
+        assert!(!contract.is_paused());
+    }
+
+    #[test]
+    fn test_accrue_emission() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        let mut context = get_context(owner);
+        testing_env!(context.block_timestamp(1_000_000_000).build());
+        let mut contract = init_contract();
+        contract.configure_emission(5.into(), "treasury.testnet".parse().unwrap());
+
+        testing_env!(context.block_timestamp(11_000_000_000).build());
+        assert_eq!(50, contract.accrue_emission().0);
+        assert_eq!(0, contract.accrue_emission().0);
+
+        testing_env!(context.block_timestamp(14_500_000_000).build());
+        assert_eq!(15, contract.accrue_emission().0);
+        assert_eq!(
+            65u128,
+            *contract
+                .balance_of("treasury.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_accrue_emission_capped_by_max_supply() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        let mut context = get_context(owner);
+        testing_env!(context.build());
+        let mut contract = init_contract();
+        contract.set_max_supply(Some((TOTAL_SUPPLY + 20).into()));
+        contract.configure_emission(5.into(), "treasury.testnet".parse().unwrap());
+
+        testing_env!(context.block_timestamp(10_000_000_000).build());
+        assert_eq!(20, contract.accrue_emission().0);
+        assert_eq!(TOTAL_SUPPLY + 20, *contract.total_supply());
+    }
+
+    #[test]
+    #[should_panic(expected = "max supply exceeded")]
+    fn test_mint_above_max_supply() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.set_max_supply(Some((TOTAL_SUPPLY + 20).into()));
+        contract.mint("test.testnet".parse().unwrap(), 21.into());
+    }
+
+    fn setup_can_transfer() -> ERC20 {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract
+    }
+
+    fn can_transfer(contract: &ERC20, amount: u128) -> bool {
+        contract.can_transfer(
+            "nutinaguti.testnet".parse().unwrap(),
+            "test.testnet".parse().unwrap(),
+            amount.into(),
+        )
+    }
+
+    #[test]
+    fn test_can_transfer_unrestricted() {
+        let contract = setup_can_transfer();
+        assert!(can_transfer(&contract, 100));
+    }
+
+    #[test]
+    fn test_can_transfer_paused() {
+        let mut contract = setup_can_transfer();
+        contract.pause();
+        assert!(!can_transfer(&contract, 1));
+    }
+
+    #[test]
+    fn test_can_transfer_frozen() {
+        let mut contract = setup_can_transfer();
+        contract.freeze("test.testnet".parse().unwrap());
+        assert!(!can_transfer(&contract, 1));
+    }
+
+    #[test]
+    fn test_can_transfer_locked() {
+        let mut contract = setup_can_transfer();
+        contract.set_locked("nutinaguti.testnet".parse().unwrap(), 50.into());
+        assert!(can_transfer(&contract, 50));
+        assert!(!can_transfer(&contract, 51));
+    }
+
+    #[test]
+    fn test_can_transfer_cooldown() {
+        let mut contract = setup_can_transfer();
+        contract.set_transfer_cooldown(60);
+        contract.transfer("other.testnet".parse().unwrap(), 1.into());
+        assert!(!can_transfer(&contract, 1));
+    }
+
+    #[test]
+    fn test_can_transfer_insufficient_balance() {
+        let contract = setup_can_transfer();
+        assert!(!can_transfer(&contract, 101));
+    }
+
+    #[test]
+    fn test_transfer_from_emits_approval() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.approve("test.testnet".parse().unwrap(), 50.into());
+
+        testing_env!(get_context("test.testnet".parse().unwrap()).build());
+        contract.transfer_from(
+            "nutinaguti.testnet".parse().unwrap(),
+            "test.testnet".parse().unwrap(),
+            20.into(),
+        );
+
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"new_allowance":"30","old_allowance":"50","owner":"nutinaguti.testnet","spender":"test.testnet"}],"event":"approval","standard":"nep141","version":"1.0.0"}"#,
+                r#"EVENT_JSON:{"data":[{"amount":"20","new_owner_id":"test.testnet","old_owner_id":"nutinaguti.testnet"}],"event":"ft_transfer","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    fn setup_storage_deposit(extra: Balance) -> (ERC20, Balance) {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let contract = init_contract();
+        let min = contract.storage_balance_bounds().min.0;
+
+        let mut context = get_context("test.testnet".parse().unwrap());
+        testing_env!(context.attached_deposit(min + extra).build());
+        let mut contract = contract;
+        let balance = contract.storage_deposit(None, None);
+        assert_eq!(min, balance.total.0);
+        assert_eq!(0, balance.available.0);
+        let receipts = get_created_receipts();
+        if extra > 0 {
+            assert_eq!("test.testnet", receipts[0].receiver_id.as_str());
+            assert_eq!(
+                vec![VmAction::Transfer { deposit: extra }],
+                receipts[0].actions
+            );
+        } else {
+            assert!(receipts.is_empty());
+        }
+
+        testing_env!(context.attached_deposit(1).build());
+        (contract, min)
+    }
+
+    #[test]
+    fn test_storage_withdraw_nothing_available() {
+        let (mut contract, min) = setup_storage_deposit(ONE_NEAR);
+        let balance = contract.storage_withdraw(None);
+
+        assert_eq!(min, balance.total.0);
+        assert_eq!(0, balance.available.0);
+        assert!(get_created_receipts().is_empty());
+    }
+
+    #[test]
+    fn test_storage_deposit_above_max_when_registered() {
+        let (mut contract, min) = setup_storage_deposit(0);
+        testing_env!(get_context("test.testnet".to_string())
+            .attached_deposit(ONE_NEAR)
+            .build());
+        let balance = contract.storage_deposit(None, None);
+
+        assert_eq!(min, balance.total.0);
+        let receipts = get_created_receipts();
+        assert_eq!("test.testnet", receipts[0].receiver_id.as_str());
+        assert_eq!(
+            vec![VmAction::Transfer { deposit: ONE_NEAR }],
+            receipts[0].actions
+        );
+    }
+
+    #[test]
+    fn test_storage_deposit_batch() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        let min = contract.storage_balance_bounds().min.0;
+
+        let mut context = get_context("exchange.testnet".to_string());
+        testing_env!(context.attached_deposit(3 * min + 2).build());
+        let accounts: Vec<AccountId> = ["a.testnet", "b.testnet", "c.testnet"]
+            .iter()
+            .map(|account_id| account_id.parse().unwrap())
+            .collect();
+        contract.storage_deposit_batch(accounts.clone());
+
+        for account_id in accounts {
+            assert_eq!(
+                Some(U128(min)),
+                contract
+                    .storage_balance_of(account_id.clone())
+                    .map(|balance| balance.total)
+            );
+            assert_eq!(Some(&0), contract.balance_of(account_id));
+        }
+        let receipts = get_created_receipts();
+        assert_eq!("exchange.testnet", receipts[0].receiver_id.as_str());
+        assert_eq!(vec![VmAction::Transfer { deposit: 2 }], receipts[0].actions);
+    }
+
+    #[test]
+    fn test_storage_balance_of() {
+        let (contract, min) = setup_storage_deposit(ONE_NEAR);
+        assert_eq!(
+            Some(storage::StorageBalance {
+                total: min.into(),
+                available: 0.into(),
+            }),
+            contract.storage_balance_of("test.testnet".parse().unwrap())
+        );
+        assert_eq!(
+            None,
+            contract.storage_balance_of("other.testnet".parse().unwrap())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "amount exceeds available storage balance")]
+    fn test_storage_withdraw_too_much() {
+        let (mut contract, _) = setup_storage_deposit(ONE_NEAR);
+        contract.storage_withdraw(Some(1.into()));
+    }
+
+    #[derive(Debug)]
+    struct CountingObserver(Rc<Cell<u32>>);
+
+    impl observer::TransferObserver for CountingObserver {
+        fn on_transfer(&mut self, _from: &AccountId, _to: &AccountId, _amount: u128) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn test_transfer_observer() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        let count = Rc::new(Cell::new(0));
+        contract.register_transfer_observer(Box::new(CountingObserver(count.clone())));
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+
+        contract.transfer("a.testnet".parse().unwrap(), 1.into());
+        assert_eq!(1, count.get());
+        contract.batch_transfer(vec![
+            ("b.testnet".parse().unwrap(), 1.into()),
+            ("c.testnet".parse().unwrap(), 1.into()),
+        ]);
+        assert_eq!(3, count.get());
+    }
+
+    #[test]
+    #[should_panic(expected = "burn exceeds unlocked balance")]
+    fn test_burn_locked_balance() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 100.into());
+        contract.set_locked("test.testnet".parse().unwrap(), 50.into());
+        contract.burn("test.testnet".parse().unwrap(), 51.into());
+    }
+
+    #[test]
+    fn test_burn_all_keeps_locked_balance() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 100.into());
+        contract.set_locked("test.testnet".parse().unwrap(), 50.into());
+
+        assert_eq!(50, contract.burn_all("test.testnet".parse().unwrap()).0);
+        assert_eq!(
+            50u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "receiver not registered")]
+    fn test_transfer_unregistered_panics() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.set_registration_policy(false, false);
+        contract.transfer("test.testnet".parse().unwrap(), 1.into());
+    }
+
+    #[test]
+    fn test_transfer_unregistered_returns_false() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.mint("registered.testnet".parse().unwrap(), 0.into());
+        contract.set_registration_policy(false, true);
+
+        assert!(!contract.transfer("test.testnet".parse().unwrap(), 1.into()));
+        assert!(contract
+            .balance_of("test.testnet".parse().unwrap())
+            .is_none());
+        assert!(contract.transfer("registered.testnet".parse().unwrap(), 1.into()));
+    }
+
+    #[test]
+    #[cfg(not(feature = "lookup-balances"))]
+    fn test_top_holders() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        for (holder, amount) in [
+            ("a.testnet", 10),
+            ("b.testnet", 50),
+            ("c.testnet", 30),
+            ("d.testnet", 40),
+            ("e.testnet", 20),
+        ] {
+            contract.mint(holder.parse().unwrap(), amount.into());
+        }
+
+        assert_eq!(
+            vec![
+                ("b.testnet".parse().unwrap(), 50.into()),
+                ("d.testnet".parse().unwrap(), 40.into()),
+                ("c.testnet".parse().unwrap(), 30.into()),
+            ],
+            contract.top_holders(3)
+        );
+    }
+
+    #[test]
+    fn test_migrate_balance_entries() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut legacy_balance: UnorderedMap<AccountId, u128> =
+            UnorderedMap::new(nested_key(b"t", StorageKey::Balance));
+        legacy_balance.insert("a.testnet".parse().unwrap(), 10);
+        legacy_balance.insert("b.testnet".parse().unwrap(), 20);
+        let mut legacy_locked: UnorderedMap<AccountId, u128> =
+            UnorderedMap::new(nested_key(b"t", StorageKey::Locked));
+        legacy_locked.insert("b.testnet".parse().unwrap(), 5);
+        let legacy_last_transfer_ts: UnorderedMap<AccountId, u64> =
+            UnorderedMap::new(nested_key(b"t", StorageKey::LastTransfer));
+
+        let mut contract = init_contract();
+        contract.migrate_balance_entries(legacy_balance, legacy_locked, legacy_last_transfer_ts);
+
+        assert_eq!(
+            Some(&balance::BalanceEntry {
+                amount: 10,
+                locked: 0,
+                last_transfer_ts: None,
+            }),
+            contract.balance.get(&"a.testnet".parse().unwrap())
+        );
+        assert_eq!(
+            20u128,
+            *contract.balance_of("b.testnet".parse().unwrap()).unwrap()
+        );
+        assert_eq!(5, contract.locked_of("b.testnet".parse().unwrap()).0);
+    }
+
+    #[test]
+    fn test_transfer_burn_fee() {
+        let owner: AccountId = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner.to_string()).build());
+        let mut contract = init_contract_with_supply(0);
+        contract.mint(owner.clone(), 10_000.into());
+        contract.set_burn_fee(100);
+
+        contract.transfer("test.testnet".parse().unwrap(), 10_000.into());
+
+        assert_eq!(
+            9_900u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(0u128, *contract.balance_of(owner).unwrap());
+        assert_eq!(9_900, contract.total_supply);
+        assert_eq!(
+            get_logs()[get_logs().len() - 2..],
+            [
+                r#"EVENT_JSON:{"data":[{"amount":"100","owner_id":"nutinaguti.testnet"}],"event":"ft_burn","standard":"nep141","version":"1.0.0"}"#,
+                r#"EVENT_JSON:{"data":[{"amount":"9900","new_owner_id":"test.testnet","old_owner_id":"nutinaguti.testnet"}],"event":"ft_transfer","standard":"nep141","version":"1.0.0"}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn test_batch_burn() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract_with_supply(0);
+        contract.mint("a.testnet".parse().unwrap(), 10.into());
+        contract.mint("b.testnet".parse().unwrap(), 20.into());
+        contract.mint("c.testnet".parse().unwrap(), 30.into());
+
+        contract.batch_burn(vec![
+            ("a.testnet".parse().unwrap(), 1.into()),
+            ("b.testnet".parse().unwrap(), 2.into()),
+            ("c.testnet".parse().unwrap(), 30.into()),
+        ]);
+
+        assert_eq!(27, contract.total_supply);
+        assert_eq!(
+            9u128,
+            *contract.balance_of("a.testnet".parse().unwrap()).unwrap()
+        );
+        assert_eq!(
+            18u128,
+            *contract.balance_of("b.testnet".parse().unwrap()).unwrap()
+        );
+        assert_eq!(
+            0u128,
+            *contract.balance_of("c.testnet".parse().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "insufficient balance")]
+    fn test_batch_burn_validates_combined_amount() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract_with_supply(0);
+        contract.mint("a.testnet".parse().unwrap(), 10.into());
+        contract.batch_burn(vec![
+            ("a.testnet".parse().unwrap(), 6.into()),
+            ("a.testnet".parse().unwrap(), 6.into()),
+        ]);
+    }
+
+    #[test]
+    fn test_get_allowance() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        assert_eq!(
+            U128(0),
+            contract.get_allowance(
+                "nutinaguti.testnet".parse().unwrap(),
+                "test.testnet".parse().unwrap()
+            )
+        );
+
+        contract.approve("test.testnet".parse().unwrap(), 7.into());
+        assert_eq!(
+            U128(7),
+            contract.get_allowance(
+                "nutinaguti.testnet".parse().unwrap(),
+                "test.testnet".parse().unwrap()
+            )
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "account is frozen")]
+    fn test_transfer_from_frozen() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        testing_env!(get_context(owner).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.approve("spender.testnet".parse().unwrap(), 10.into());
+        contract.freeze("nutinaguti.testnet".parse().unwrap());
+
+        testing_env!(get_context("spender.testnet".parse().unwrap()).build());
+        contract.transfer_from(
+            "nutinaguti.testnet".parse().unwrap(),
+            "test.testnet".parse().unwrap(),
+            1.into(),
+        );
+    }
+
+    #[test]
+    fn test_init_initial_balances() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let contract = ERC20::init(
+            "nutinaguti.testnet".parse().unwrap(),
+            "FUN COIN".to_string(),
+            "FUNC".to_string(),
+            DECIMALS,
+            100.into(),
+            events::DEFAULT_EVENT_STANDARD.to_string(),
+            events::DEFAULT_EVENT_VERSION.to_string(),
+            vec![
+                ("a.testnet".parse().unwrap(), 60.into()),
+                ("b.testnet".parse().unwrap(), 40.into()),
+            ],
+            false,
+            b"t",
+        );
+        assert_eq!(100, contract.total_supply);
+        assert_eq!(
+            60u128,
+            *contract.balance_of("a.testnet".parse().unwrap()).unwrap()
+        );
+        assert_eq!(
+            40u128,
+            *contract.balance_of("b.testnet".parse().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "initial balances must sum to total_supply")]
+    fn test_init_initial_balances_mismatch() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        ERC20::init(
+            "nutinaguti.testnet".parse().unwrap(),
+            "FUN COIN".to_string(),
+            "FUNC".to_string(),
+            DECIMALS,
+            100.into(),
+            events::DEFAULT_EVENT_STANDARD.to_string(),
+            events::DEFAULT_EVENT_VERSION.to_string(),
+            vec![("a.testnet".parse().unwrap(), 99.into())],
+            false,
+            b"t",
+        );
+    }
+
+    #[test]
+    fn test_near_withdraw() {
+        let mut context = get_context("test.testnet".to_string());
+        testing_env!(context.attached_deposit(ONE_NEAR).build());
+        let mut contract = init_contract_with_supply(0);
+        contract.near_deposit();
+        assert_eq!(ONE_NEAR, contract.total_supply);
+
+        testing_env!(context.attached_deposit(1).build());
+        drop(contract.near_withdraw(ONE_NEAR.into()));
+        assert_eq!(0, contract.total_supply);
+        assert_eq!(0, contract.wrapped_supply().0);
+        assert_eq!(
+            0u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+        let receipts = get_created_receipts();
+        assert_eq!("test.testnet", receipts[0].receiver_id.as_str());
+        assert_eq!(
+            vec![VmAction::Transfer { deposit: ONE_NEAR }],
+            receipts[0].actions
+        );
+
+        testing_env!(
+            get_context("erc20.testnet".to_string()).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![])],
+        );
+        assert!(contract.resolve_withdraw("test.testnet".parse().unwrap(), ONE_NEAR.into()));
+        assert_eq!(0, contract.total_supply);
+    }
+
+    #[test]
+    #[should_panic(expected = "amount exceeds wrapped balance")]
+    fn test_near_withdraw_unwrapped_tokens() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract_with_supply(0);
+        contract.mint("nutinaguti.testnet".parse().unwrap(), ONE_NEAR.into());
+        testing_env!(context.attached_deposit(1).build());
+        drop(contract.near_withdraw(ONE_NEAR.into()));
+    }
+
+    #[test]
+    #[should_panic(expected = "max supply exceeded")]
+    fn test_near_deposit_respects_max_supply() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract_with_supply(0);
+        contract.set_max_supply(Some(10.into()));
+        testing_env!(context.attached_deposit(1000).build());
+        contract.near_deposit();
+    }
+
+    #[test]
+    #[should_panic(expected = "minting is paused")]
+    fn test_near_deposit_while_minting_paused() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract_with_supply(0);
+        contract.pause_minting();
+        testing_env!(context.attached_deposit(1000).build());
+        contract.near_deposit();
+    }
+
+    #[test]
+    fn test_resolve_withdraw_failed_transfer() {
+        let mut context = get_context("test.testnet".to_string());
+        testing_env!(context.attached_deposit(ONE_NEAR).build());
+        let mut contract = init_contract_with_supply(0);
+        contract.near_deposit();
+        testing_env!(context.attached_deposit(1).build());
+        drop(contract.near_withdraw(ONE_NEAR.into()));
+
+        testing_env!(
+            get_context("erc20.testnet".to_string()).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        assert!(!contract.resolve_withdraw("test.testnet".parse().unwrap(), ONE_NEAR.into()));
+        assert_eq!(ONE_NEAR, contract.total_supply);
+        assert_eq!(ONE_NEAR, contract.wrapped_supply().0);
+        assert_eq!(
+            U128(ONE_NEAR),
+            contract.wrapped_balance_of("test.testnet".parse().unwrap())
+        );
+        assert_eq!(
+            ONE_NEAR,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_contract_stats() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.block_timestamp(42).build());
+        let mut contract = init_contract_with_supply(0);
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        contract.transfer("test.testnet".parse().unwrap(), 4.into());
+        contract.pause();
+
+        let stats = contract.contract_stats();
+        assert_eq!(2, stats.holders);
+        assert_eq!(U128(10), stats.total_supply);
+        assert_eq!(contract.storage_usage(), stats.storage_usage);
+        assert!(stats.paused);
+        assert_eq!(42, stats.block_timestamp);
+    }
+
+    #[test]
+    fn test_transfer_while_minting_paused() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        contract.pause_minting();
+
+        contract.transfer("test.testnet".parse().unwrap(), 4.into());
+        assert_eq!(
+            4u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "minting is paused")]
+    fn test_mint_while_minting_paused() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.pause_minting();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+    }
+
+    #[test]
+    fn test_sweep_expired_allowances() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = init_contract();
+        contract.approve_until("spender.testnet".parse().unwrap(), 10.into(), 100);
+        contract.approve_until("other.testnet".parse().unwrap(), 10.into(), 1_000);
+
+        testing_env!(context.block_timestamp(100).build());
+        assert_eq!(
+            0u128,
+            *contract.allowance(
+                "nutinaguti.testnet".parse().unwrap(),
+                "spender.testnet".parse().unwrap()
+            )
+        );
+        let swept = contract.sweep_expired_allowances(
+            "nutinaguti.testnet".parse().unwrap(),
+            vec![
+                "spender.testnet".parse().unwrap(),
+                "other.testnet".parse().unwrap(),
+            ],
+        );
+
+        assert_eq!(1, swept);
+        let allowed = contract
+            .allowed
+            .get(&"nutinaguti.testnet".parse().unwrap())
+            .unwrap();
+        assert!(!allowed.contains_key(&"spender.testnet".parse().unwrap()));
+        assert!(allowed.contains_key(&"other.testnet".parse().unwrap()));
+    }
+
+    #[test]
+    #[should_panic(expected = "balance below minimum")]
+    fn test_transfer_below_min_balance() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        contract.set_min_balance(5.into());
+        contract.transfer("test.testnet".parse().unwrap(), 6.into());
+    }
+
+    #[test]
+    fn test_transfer_full_balance_with_min_balance() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        contract.set_min_balance(5.into());
+        contract.transfer("test.testnet".parse().unwrap(), 10.into());
+
+        assert!(contract
+            .balance_of("nutinaguti.testnet".parse().unwrap())
+            .is_none());
+        assert_eq!(
+            10u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_ft_transfer_refunds_storage_on_empty() {
+        let mut context = get_context("test.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract();
+        let deposit = contract.storage_balance_bounds().min.0;
+        testing_env!(context.attached_deposit(deposit).build());
+        contract.storage_deposit(None, None);
+
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        contract.mint("test.testnet".parse().unwrap(), 10.into());
+        contract.set_refund_storage_on_empty(true);
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer("other.testnet".parse().unwrap(), 10.into(), None, None);
+
+        assert!(contract
+            .balance_of("test.testnet".parse().unwrap())
+            .is_none());
+        assert!(!contract
+            .storage_deposits
+            .contains_key(&"test.testnet".parse().unwrap()));
+        let receipts = get_created_receipts();
+        assert_eq!(1, receipts.len());
+        assert_eq!("test.testnet", receipts[0].receiver_id.as_str());
+        assert_eq!(vec![VmAction::Transfer { deposit }], receipts[0].actions);
+    }
+
+    fn test_keypair() -> ed25519_dalek::Keypair {
+        let secret = ed25519_dalek::SecretKey::from_bytes(&[7u8; 32]).unwrap();
+        let public = ed25519_dalek::PublicKey::from(&secret);
+        ed25519_dalek::Keypair { secret, public }
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        use ed25519_dalek::Signer;
+
+        let keypair = test_keypair();
+        let message = b"transfer 10".to_vec();
+        let signature = keypair.sign(&message).to_bytes().to_vec();
+        let public_key = keypair.public.to_bytes().to_vec();
+        assert!(signature::verify_signature(
+            message.clone(),
+            signature.clone(),
+            public_key.clone()
+        ));
+
+        let mut forged = signature;
+        forged[0] ^= 1;
+        assert!(!signature::verify_signature(message, forged, public_key));
+    }
+
+    fn signed_permit(contract: &ERC20) -> Vec<u8> {
+        use ed25519_dalek::Signer;
+
+        let message = contract.permit_message(
+            "nutinaguti.testnet".parse().unwrap(),
+            "spender.testnet".parse().unwrap(),
+            10.into(),
+            0,
+            100,
+        );
+        test_keypair().sign(&message).to_bytes().to_vec()
+    }
+
+    #[test]
+    fn test_permit() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context
+            .current_account_id("erc20.testnet".parse().unwrap())
+            .build());
+        let mut contract = init_contract();
+        contract.set_permit_key(test_keypair().public.to_bytes().to_vec());
+        let signature = signed_permit(&contract);
+
+        testing_env!(get_context("relayer.testnet".to_string())
+            .current_account_id("erc20.testnet".parse().unwrap())
+            .build());
+        contract.permit(
+            "nutinaguti.testnet".parse().unwrap(),
+            "spender.testnet".parse().unwrap(),
+            10.into(),
+            100,
+            signature,
+        );
+        assert_eq!(
+            10u128,
+            *contract.allowance(
+                "nutinaguti.testnet".parse().unwrap(),
+                "spender.testnet".parse().unwrap()
+            )
+        );
+        assert_eq!(
+            1,
+            contract.permit_nonce("nutinaguti.testnet".parse().unwrap())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid signature")]
+    fn test_permit_rejected_for_other_contract() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context
+            .current_account_id("erc20.testnet".parse().unwrap())
+            .build());
+        let mut contract = init_contract();
+        contract.set_permit_key(test_keypair().public.to_bytes().to_vec());
+        let signature = signed_permit(&contract);
+
+        testing_env!(context
+            .current_account_id("fork.testnet".parse().unwrap())
+            .build());
+        contract.permit(
+            "nutinaguti.testnet".parse().unwrap(),
+            "spender.testnet".parse().unwrap(),
+            10.into(),
+            100,
+            signature,
+        );
+    }
+
+    #[test]
+    fn test_roles() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.add_minter("minter.testnet".parse().unwrap());
+        assert_eq!(1, contract.minters_count());
+
+        assert_eq!(
+            roles::RoleFlags {
+                owner: true,
+                minter: true,
+                pauser: true,
+            },
+            contract.roles("nutinaguti.testnet".parse().unwrap())
+        );
+        assert_eq!(
+            roles::RoleFlags {
+                owner: false,
+                minter: true,
+                pauser: false,
+            },
+            contract.roles("minter.testnet".parse().unwrap())
+        );
+        assert_eq!(
+            roles::RoleFlags {
+                owner: false,
+                minter: false,
+                pauser: false,
+            },
+            contract.roles("test.testnet".parse().unwrap())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "only minter")]
+    fn test_mint_not_minter() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        testing_env!(get_context("test.testnet".to_string()).build());
+        contract.mint("test.testnet".parse().unwrap(), 1.into());
+    }
+
+    #[test]
+    fn test_transfer_no_deposit_allowlisted() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("dex.testnet".parse().unwrap(), 10.into());
+        contract.set_no_deposit_caller("dex.testnet".parse().unwrap(), true);
+
+        testing_env!(get_context("dex.testnet".to_string()).build());
+        contract.transfer_no_deposit("test.testnet".parse().unwrap(), 4.into(), None);
+        assert_eq!(
+            4u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "caller not allowlisted")]
+    fn test_transfer_no_deposit_not_allowlisted() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("dex.testnet".parse().unwrap(), 10.into());
+
+        testing_env!(get_context("dex.testnet".to_string()).build());
+        contract.transfer_no_deposit("test.testnet".parse().unwrap(), 4.into(), None);
+    }
+
+    #[test]
+    fn test_burn_fee_tiny_transfer() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract_with_supply(0);
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.set_burn_fee(100);
+        contract.set_burn_fee_bounds(2.into(), Some(50.into()));
+
+        contract.transfer("test.testnet".parse().unwrap(), 10.into());
+        assert_eq!(
+            8u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+
+        contract.transfer("other.testnet".parse().unwrap(), 1.into());
+        assert_eq!(
+            0u128,
+            *contract
+                .balance_of("other.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(97, contract.total_supply);
+    }
+
+    #[test]
+    fn test_burn_fee_large_transfer() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract_with_supply(0);
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 1_000_000.into());
+        contract.set_burn_fee(100);
+        contract.set_burn_fee_bounds(2.into(), Some(50.into()));
+
+        contract.transfer("test.testnet".parse().unwrap(), 1_000_000.into());
+        assert_eq!(
+            999_950u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(999_950, contract.total_supply);
+    }
+
+    #[test]
+    fn test_balance_of_scaled() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint(
+            "test.testnet".parse().unwrap(),
+            1_234_567_890_123_456_789u128.into(),
+        );
+
+        assert_eq!(
+            U128(1_234_567),
+            contract.balance_of_scaled("test.testnet".parse().unwrap(), 6)
+        );
+        assert_eq!(
+            U128(12_345_678_901_234_567_890),
+            contract.balance_of_scaled("test.testnet".parse().unwrap(), 19)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot transfer to token contract")]
+    fn test_transfer_to_token_contract() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context
+            .current_account_id("erc20.testnet".parse().unwrap())
+            .build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        contract.transfer("erc20.testnet".parse().unwrap(), 1.into());
+    }
+
+    #[test]
+    fn test_batch_transfer_at_max_size() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        contract.set_max_batch_size(2);
+        contract.batch_transfer(vec![
+            ("a.testnet".parse().unwrap(), 1.into()),
+            ("b.testnet".parse().unwrap(), 1.into()),
+        ]);
+        assert_eq!(
+            8u128,
+            *contract
+                .balance_of("nutinaguti.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "batch exceeds max size of 2")]
+    fn test_batch_transfer_over_max_size() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        contract.set_max_batch_size(2);
+        contract.batch_transfer(vec![
+            ("a.testnet".parse().unwrap(), 1.into()),
+            ("b.testnet".parse().unwrap(), 1.into()),
+            ("c.testnet".parse().unwrap(), 1.into()),
+        ]);
+    }
+
+    #[test]
+    fn test_allowance_history() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.block_timestamp(1).build());
+        let mut contract = init_contract();
+        contract.approve("spender.testnet".parse().unwrap(), 10.into());
+        testing_env!(context.block_timestamp(2).build());
+        contract.increase_allowance("spender.testnet".parse().unwrap(), 5.into());
+        testing_env!(context.block_timestamp(3).build());
+        contract.decrease_allowance("spender.testnet".parse().unwrap(), 12.into());
+
+        let history = contract.allowance_history(
+            "nutinaguti.testnet".parse().unwrap(),
+            "spender.testnet".parse().unwrap(),
+            10,
+        );
+        let history: Vec<(u128, u64)> = history
+            .into_iter()
+            .map(|change| (change.value.0, change.timestamp))
+            .collect();
+        assert_eq!(vec![(10, 1), (15, 2), (3, 3)], history);
+    }
+
+    #[test]
+    fn test_global_hook_scheduled() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        contract.set_global_hook(Some("rewards.testnet".parse().unwrap()));
+        contract.transfer("test.testnet".parse().unwrap(), 1.into());
+
+        let receipts = get_created_receipts();
+        assert_eq!(1, receipts.len());
+        assert_eq!("rewards.testnet", receipts[0].receiver_id.as_str());
+        assert!(matches!(
+            &receipts[0].actions[0],
+            VmAction::FunctionCall { function_name, .. } if function_name == "on_any_transfer"
+        ));
+    }
+
+    #[test]
+    fn test_global_hook_unset() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        contract.transfer("test.testnet".parse().unwrap(), 1.into());
+        assert!(get_created_receipts().is_empty());
+    }
+
+    #[test]
+    fn test_execute_recovery_after_delay() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = init_contract();
+        contract.mint("lost.testnet".parse().unwrap(), 10.into());
+        let request_id = contract.request_recovery(
+            "lost.testnet".parse().unwrap(),
+            "new.testnet".parse().unwrap(),
+        );
+
+        testing_env!(context
+            .block_timestamp(recovery::RECOVERY_DELAY_SECS * NANOS_PER_SEC)
+            .build());
+        assert_eq!(U128(10), contract.execute_recovery(request_id));
+        assert_eq!(
+            0u128,
+            *contract
+                .balance_of("lost.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            10u128,
+            *contract.balance_of("new.testnet".parse().unwrap()).unwrap()
+        );
+        assert!(contract.recovery_request(request_id).is_none());
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"10","new_owner_id":"new.testnet","old_owner_id":"lost.testnet"}],"event":"ft_transfer","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "recovery timelock active")]
+    fn test_execute_recovery_before_delay() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = init_contract();
+        contract.mint("lost.testnet".parse().unwrap(), 10.into());
+        let request_id = contract.request_recovery(
+            "lost.testnet".parse().unwrap(),
+            "new.testnet".parse().unwrap(),
+        );
+        contract.execute_recovery(request_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "no recovery request")]
+    fn test_cancel_recovery_veto() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = init_contract();
+        contract.mint("lost.testnet".parse().unwrap(), 10.into());
+        let request_id = contract.request_recovery(
+            "lost.testnet".parse().unwrap(),
+            "new.testnet".parse().unwrap(),
+        );
+
+        testing_env!(get_context("lost.testnet".to_string()).build());
+        contract.cancel_recovery(request_id);
+        assert!(contract.recovery_request(request_id).is_none());
+
+        testing_env!(get_context("nutinaguti.testnet".to_string())
+            .block_timestamp(recovery::RECOVERY_DELAY_SECS * NANOS_PER_SEC)
+            .build());
+        contract.execute_recovery(request_id);
+    }
+
+    #[test]
+    #[cfg(not(feature = "lookup-balances"))]
+    fn test_redenominate() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract_with_supply(0);
+        contract.mint(
+            "a.testnet".parse().unwrap(),
+            3_000_000_000_000_000_000u128.into(),
+        );
+        contract.mint(
+            "b.testnet".parse().unwrap(),
+            1_500_000_999_999_999_999u128.into(),
+        );
+        contract.redenominate(6);
+        assert!(contract.redenominate_batch(100));
+
+        assert_eq!(6, contract.decimals);
+        assert_eq!(
+            3_000_000u128,
+            *contract.balance_of("a.testnet".parse().unwrap()).unwrap()
+        );
+        assert_eq!(
+            1_500_000u128,
+            *contract.balance_of("b.testnet".parse().unwrap()).unwrap()
+        );
+        assert_eq!(4_500_000, contract.total_supply);
+    }
+
+    #[test]
+    fn test_approve_many() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        let spenders: Vec<AccountId> = (0..5)
+            .map(|i| format!("spender{}.testnet", i).parse().unwrap())
+            .collect();
+        contract.approve_many(
+            spenders
+                .iter()
+                .enumerate()
+                .map(|(i, spender)| (spender.clone(), U128(i as u128 + 1)))
+                .collect(),
+        );
+        testing_env!(get_context("other.testnet".to_string()).build());
+        contract.approve(spenders[0].clone(), 100.into());
+
+        for (i, spender) in spenders.iter().enumerate() {
+            assert_eq!(
+                i as u128 + 1,
+                *contract.allowance("nutinaguti.testnet".parse().unwrap(), spender.clone())
+            );
+        }
+        assert_eq!(
+            100u128,
+            *contract.allowance("other.testnet".parse().unwrap(), spenders[0].clone())
+        );
+        assert_eq!(
+            U128(15),
+            contract.total_approved("nutinaguti.testnet".parse().unwrap())
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "lookup-balances"))]
+    fn test_concentration() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract_with_supply(0);
+        contract.mint("a.testnet".parse().unwrap(), 50.into());
+        contract.mint("b.testnet".parse().unwrap(), 30.into());
+        contract.mint("c.testnet".parse().unwrap(), 15.into());
+        contract.mint("d.testnet".parse().unwrap(), 5.into());
+
+        assert_eq!(5_000, contract.concentration(1));
+        assert_eq!(8_000, contract.concentration(2));
+        assert_eq!(10_000, contract.concentration(10));
+    }
+
+    #[test]
+    fn test_permit_transfer_from() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context
+            .current_account_id("erc20.testnet".parse().unwrap())
+            .build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        contract.set_permit_key(test_keypair().public.to_bytes().to_vec());
+        let signature = signed_permit(&contract);
+
+        testing_env!(get_context("spender.testnet".to_string())
+            .current_account_id("erc20.testnet".parse().unwrap())
+            .build());
+        assert!(contract.permit_transfer_from(
+            "nutinaguti.testnet".parse().unwrap(),
+            "test.testnet".parse().unwrap(),
+            10.into(),
+            100,
+            signature,
+        ));
+        assert_eq!(
+            10u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            1,
+            contract.permit_nonce("nutinaguti.testnet".parse().unwrap())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "token is non-transferable")]
+    fn test_transfer_soulbound() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.set_transferable(false);
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        contract.transfer("test.testnet".parse().unwrap(), 1.into());
+    }
+
+    #[test]
+    #[should_panic(expected = "token is non-transferable")]
+    fn test_ft_transfer_soulbound() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract();
+        contract.set_transferable(false);
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer("test.testnet".parse().unwrap(), 1.into(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "token is non-transferable")]
+    fn test_transfer_from_soulbound() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.set_transferable(false);
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        contract.approve("spender.testnet".parse().unwrap(), 5.into());
+
+        testing_env!(get_context("spender.testnet".to_string()).build());
+        contract.transfer_from(
+            "nutinaguti.testnet".parse().unwrap(),
+            "test.testnet".parse().unwrap(),
+            1.into(),
+        );
+    }
+
+    #[test]
+    fn test_mint_and_burn_soulbound() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract_with_supply(0);
+        contract.set_transferable(false);
+        contract.mint("test.testnet".parse().unwrap(), 10.into());
+        contract.burn("test.testnet".parse().unwrap(), 4.into());
+        assert_eq!(
+            6u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(6, contract.total_supply);
+    }
+
+    #[test]
+    #[cfg(not(feature = "lookup-balances"))]
+    fn test_supply_breakdown() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract_with_supply(0);
+        contract.mint("a.testnet".parse().unwrap(), 100.into());
+        contract.mint("b.testnet".parse().unwrap(), 50.into());
+        contract.set_locked("a.testnet".parse().unwrap(), 30.into());
+        contract.set_locked("b.testnet".parse().unwrap(), 10.into());
+        contract.burn("b.testnet".parse().unwrap(), 20.into());
+
+        assert_eq!(
+            SupplyBreakdown {
+                total: U128(130),
+                circulating: U128(90),
+                locked: U128(40),
+                burned: U128(20),
+            },
+            contract.supply_breakdown()
+        );
+    }
+
+    #[test]
+    fn test_total_burned() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract_with_supply(0);
+        contract.mint("test.testnet".parse().unwrap(), 10.into());
+        contract.burn("test.testnet".parse().unwrap(), 3.into());
+        contract.burn("test.testnet".parse().unwrap(), 4.into());
+        assert_eq!(U128(7), contract.total_burned());
+        assert_eq!(3, *contract.total_supply());
+    }
+
+    fn setup_access_lists() -> ERC20 {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        contract.set_allowlisted("both.testnet".parse().unwrap(), true);
+        contract.set_denylisted("both.testnet".parse().unwrap(), true);
+        contract.set_allowlisted("allowed.testnet".parse().unwrap(), true);
+        contract
+    }
+
+    #[test]
+    #[should_panic(expected = "receiver not allowed")]
+    fn test_transfer_to_allowlisted_and_denylisted() {
+        let mut contract = setup_access_lists();
+        contract.transfer("both.testnet".parse().unwrap(), 1.into());
+    }
+
+    #[test]
+    fn test_transfer_to_allowlisted() {
+        let mut contract = setup_access_lists();
+        contract.set_whitelist_mode(true);
+        contract.transfer("allowed.testnet".parse().unwrap(), 1.into());
+        assert_eq!(
+            1u128,
+            *contract
+                .balance_of("allowed.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_transfer_to_unlisted() {
+        let mut contract = setup_access_lists();
+        assert!(contract.can_receive(&"test.testnet".parse().unwrap()));
+        contract.transfer("test.testnet".parse().unwrap(), 1.into());
+
+        contract.set_whitelist_mode(true);
+        assert!(!contract.can_receive(&"test.testnet".parse().unwrap()));
+    }
+
+    #[test]
+    #[should_panic(expected = "receiver not allowed")]
+    fn test_transfer_to_unlisted_in_whitelist_mode() {
+        let mut contract = setup_access_lists();
+        contract.set_whitelist_mode(true);
+        contract.transfer("test.testnet".parse().unwrap(), 1.into());
+    }
+
+    #[test]
+    fn test_storage_staking_reserve_grows() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        let reserve = contract.storage_staking_reserve().0;
+        contract.mint("test.testnet".parse().unwrap(), 1.into());
+        contract.balance.flush();
+        assert!(contract.storage_staking_reserve().0 > reserve);
+    }
+
+    #[test]
+    fn test_withdraw_near_above_reserve() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract();
+        let reserve = 1_000 * env::storage_byte_cost();
+        testing_env!(context
+            .storage_usage(1_000)
+            .account_balance(reserve + 10)
+            .build());
+        assert_eq!(U128(reserve), contract.storage_staking_reserve());
+        drop(contract.withdraw_near("treasury.testnet".parse().unwrap(), 10.into()));
+        let receipts = get_created_receipts();
+        assert_eq!(
+            vec![VmAction::Transfer { deposit: 10 }],
+            receipts[0].actions
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "withdrawal would breach storage reserve")]
+    fn test_withdraw_near_below_reserve() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract();
+        let reserve = 1_000 * env::storage_byte_cost();
+        testing_env!(context
+            .storage_usage(1_000)
+            .account_balance(reserve + 10)
+            .build());
+        assert_eq!(U128(reserve), contract.storage_staking_reserve());
+        drop(contract.withdraw_near("treasury.testnet".parse().unwrap(), 11.into()));
+    }
+
+    #[test]
+    fn test_ft_transfer_with_referrer() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.fund_referral_pool(5.into());
+        contract.configure_referral(true, 2.into());
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(
+            "test.testnet".parse().unwrap(),
+            10.into(),
+            None,
+            Some("referrer.testnet".parse().unwrap()),
+        );
+
+        assert_eq!(
+            2u128,
+            *contract
+                .balance_of("referrer.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(U128(3), contract.referral_pool());
+        assert_eq!(
+            85u128,
+            *contract
+                .balance_of("nutinaguti.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            r#"EVENT_JSON:{"data":[{"amount":"2","new_owner_id":"referrer.testnet","old_owner_id":"alice.near"}],"event":"ft_transfer","standard":"nep141","version":"1.0.0"}"#,
+            get_logs().last().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_ft_transfer_with_frozen_referrer() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.fund_referral_pool(5.into());
+        contract.configure_referral(true, 2.into());
+        contract.freeze("referrer.testnet".parse().unwrap());
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(
+            "test.testnet".parse().unwrap(),
+            10.into(),
+            None,
+            Some("referrer.testnet".parse().unwrap()),
+        );
+
+        assert_eq!(
+            None,
+            contract.balance_of("referrer.testnet".parse().unwrap())
+        );
+        assert_eq!(U128(5), contract.referral_pool());
+    }
+
+    #[test]
+    fn test_fund_and_withdraw_referral_pool() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        contract.fund_referral_pool(5.into());
+        contract.withdraw_referral_pool(3.into());
+
+        assert_eq!(U128(2), contract.referral_pool());
+        assert_eq!(
+            98u128,
+            *contract
+                .balance_of("nutinaguti.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"5","new_owner_id":"alice.near","old_owner_id":"nutinaguti.testnet"}],"event":"ft_transfer","standard":"nep141","version":"1.0.0"}"#,
+                r#"EVENT_JSON:{"data":[{"amount":"3","new_owner_id":"nutinaguti.testnet","old_owner_id":"alice.near"}],"event":"ft_transfer","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "amount exceeds referral pool")]
+    fn test_withdraw_referral_pool_too_much() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.fund_referral_pool(5.into());
+        contract.withdraw_referral_pool(6.into());
+    }
+
+    #[test]
+    #[should_panic(expected = "all accounts are frozen")]
+    fn test_transfer_emergency_freeze_all() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 10.into());
+        contract.emergency_freeze_all();
+
+        testing_env!(get_context("test.testnet".to_string()).build());
+        contract.transfer("other.testnet".parse().unwrap(), 1.into());
+    }
+
+    #[test]
+    fn test_transfer_after_emergency_unfreeze_all() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 10.into());
+        contract.emergency_freeze_all();
+        contract.emergency_unfreeze_all();
+
+        testing_env!(get_context("test.testnet".to_string()).build());
+        contract.transfer("other.testnet".parse().unwrap(), 1.into());
+        assert_eq!(
+            1u128,
+            *contract
+                .balance_of("other.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_batch_transfer_batched_event() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        contract.set_batch_events(true);
+        contract.batch_transfer(vec![
+            ("a.testnet".parse().unwrap(), 1.into()),
+            ("b.testnet".parse().unwrap(), 2.into()),
+        ]);
+        assert_eq!(
+            get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"data":[{"amount":"1","new_owner_id":"a.testnet","old_owner_id":"nutinaguti.testnet"},{"amount":"2","new_owner_id":"b.testnet","old_owner_id":"nutinaguti.testnet"}],"event":"ft_transfer","standard":"nep141","version":"1.0.0"}"#
+        );
+    }
+
+    #[test]
+    fn test_mint_batch_events() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        let accounts = vec![
+            ("a.testnet".parse().unwrap(), 1.into()),
+            ("b.testnet".parse().unwrap(), 2.into()),
+        ];
+        contract.mint_batch(accounts.clone());
+        assert_eq!(2, get_logs().len());
+
+        contract.set_batch_events(true);
+        contract.mint_batch(accounts);
+        assert_eq!(
+            get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"data":[{"amount":"1","owner_id":"a.testnet"},{"amount":"2","owner_id":"b.testnet"}],"event":"ft_mint","standard":"nep141","version":"1.0.0"}"#
+        );
+    }
+
+    #[test]
+    fn test_account_history() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.block_timestamp(1).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 10.into());
+
+        testing_env!(get_context("test.testnet".to_string())
+            .block_timestamp(2)
+            .build());
+        contract.transfer("other.testnet".parse().unwrap(), 4.into());
+
+        testing_env!(context.block_timestamp(3).build());
+        contract.mint("other.testnet".parse().unwrap(), 5.into());
+        testing_env!(get_context("other.testnet".to_string())
+            .block_timestamp(3)
+            .build());
+        contract.transfer("test.testnet".parse().unwrap(), 2.into());
+
+        use account_history::{AccountOperation, OpType};
+        assert_eq!(
+            vec![
+                AccountOperation {
+                    op_type: OpType::Mint,
+                    counterparty: None,
+                    amount: U128(10),
+                    timestamp: 1,
+                },
+                AccountOperation {
+                    op_type: OpType::Send,
+                    counterparty: Some("other.testnet".parse().unwrap()),
+                    amount: U128(4),
+                    timestamp: 2,
+                },
+                AccountOperation {
+                    op_type: OpType::Receive,
+                    counterparty: Some("other.testnet".parse().unwrap()),
+                    amount: U128(2),
+                    timestamp: 3,
+                },
+            ],
+            contract.account_history("test.testnet".parse().unwrap(), 10)
+        );
+    }
+
+    fn setup_mint_multisig() -> ERC20 {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract_with_supply(0);
+        contract.add_minter("minter1.testnet".parse().unwrap());
+        contract.add_minter("minter2.testnet".parse().unwrap());
+        contract.configure_mint_multisig(Some(100.into()), 2);
+        contract
+    }
+
+    #[test]
+    fn test_mint_below_threshold() {
+        let mut contract = setup_mint_multisig();
+        testing_env!(get_context("minter1.testnet".to_string()).build());
+        contract.mint("test.testnet".parse().unwrap(), 100.into());
+        assert_eq!(
+            100u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "mint above threshold requires approval")]
+    fn test_mint_above_threshold() {
+        let mut contract = setup_mint_multisig();
+        testing_env!(get_context("minter1.testnet".to_string()).build());
+        contract.mint("test.testnet".parse().unwrap(), 101.into());
+    }
+
+    #[test]
+    fn test_propose_mint_two_approvals() {
+        let mut contract = setup_mint_multisig();
+        testing_env!(get_context("minter1.testnet".to_string()).build());
+        let proposal_id = contract.propose_mint("test.testnet".parse().unwrap(), 500.into());
+        assert!(contract
+            .balance_of("test.testnet".parse().unwrap())
+            .is_none());
+        assert_eq!(
+            1,
+            contract.mint_proposal(proposal_id).unwrap().approvals.len()
+        );
+
+        testing_env!(get_context("minter2.testnet".to_string()).build());
+        contract.approve_mint(proposal_id);
+        assert_eq!(
+            500u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert!(contract.mint_proposal(proposal_id).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "already approved")]
+    fn test_approve_mint_twice() {
+        let mut contract = setup_mint_multisig();
+        testing_env!(get_context("minter1.testnet".to_string()).build());
+        let proposal_id = contract.propose_mint("test.testnet".parse().unwrap(), 500.into());
+        contract.approve_mint(proposal_id);
+    }
+
+    #[test]
+    fn test_total_approved_saturates() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.approve("a.testnet".parse().unwrap(), u128::MAX.into());
+        contract.approve("b.testnet".parse().unwrap(), u128::MAX.into());
+        assert_eq!(
+            U128(u128::MAX),
+            contract.total_approved("nutinaguti.testnet".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_account_entry_size() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 1.into());
+        // 4-byte length + 12-byte id, 16 + 16 bytes of amounts, 1-byte None.
+        assert_eq!(
+            49,
+            contract.account_entry_size("test.testnet".parse().unwrap())
+        );
+        assert_eq!(
+            0,
+            contract.account_entry_size("other.testnet".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_transfer_display() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint(
+            "nutinaguti.testnet".parse().unwrap(),
+            (2 * 10u128.pow(18)).into(),
+        );
+        contract.transfer_display("test.testnet".parse().unwrap(), "1.5".to_string());
+        assert_eq!(
+            15 * 10u128.pow(17),
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "display amount too precise")]
+    fn test_transfer_display_too_precise() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint(
+            "nutinaguti.testnet".parse().unwrap(),
+            (2 * 10u128.pow(18)).into(),
+        );
+        contract.transfer_display(
+            "test.testnet".parse().unwrap(),
+            "1.0000000000000000001".to_string(),
+        );
+    }
+
+    #[test]
+    fn test_parse_display_amount_malformed() {
+        for amount in ["", ".5", "1.", "1.2.3", "-1", "1e18", " 1"] {
+            assert!(
+                std::panic::catch_unwind(|| parse_display_amount(amount, 18)).is_err(),
+                "{amount:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "lookup-balances"))]
+    fn test_self_check() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 10.into());
+        contract.approve("test.testnet".parse().unwrap(), 5.into());
+        assert!(contract.self_check().is_empty());
+
+        contract
+            .balance
+            .get_mut(&"test.testnet".parse().unwrap())
+            .unwrap()
+            .locked = 20;
+        contract.internal_set_balance(&"evil.testnet".parse().unwrap(), TOTAL_SUPPLY + 1);
+        contract
+            .allowed
+            .get_mut(&"nutinaguti.testnet".parse().unwrap())
+            .unwrap()
+            .clear();
+        assert_eq!(
+            vec![
+                "test.testnet has 20 locked but a balance of 10".to_string(),
+                format!(
+                    "balances sum to {}, above total_supply {}",
+                    TOTAL_SUPPLY + 11,
+                    TOTAL_SUPPLY + 10
+                ),
+                "allowance map of nutinaguti.testnet has no spenders".to_string(),
+            ],
+            contract.self_check()
+        );
+    }
+
+    #[test]
+    fn test_balance_backend_storage_writes() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        let holders: Vec<AccountId> = (0..10)
+            .map(|i| format!("holder{i}.testnet").parse().unwrap())
+            .collect();
+        // The first new holder may reuse storage freed by
+        // `measure_account_storage_usage`, so the baseline is the second.
+        contract.mint(holders[0].clone(), 10.into());
+        contract.flush_balances();
+        let initial = env::storage_usage();
+        contract.mint(holders[1].clone(), 10.into());
+        contract.flush_balances();
+        let per_holder = env::storage_usage() - initial;
+
+        let initial = env::storage_usage();
+        for account_id in &holders[2..] {
+            contract.mint(account_id.clone(), 10.into());
+        }
+        contract.flush_balances();
+        assert_eq!(
+            (holders.len() - 2) as u64 * per_holder,
+            env::storage_usage() - initial
+        );
+        assert!(per_holder <= contract.account_storage_usage);
+
+        let initial = env::storage_usage();
+        for (i, account_id) in holders.iter().enumerate() {
+            testing_env!(get_context(account_id.to_string())
+                .storage_usage(env::storage_usage())
+                .build());
+            contract.transfer(holders[(i + 1) % holders.len()].clone(), 1.into());
+        }
+        contract.flush_balances();
+        assert_eq!(initial, env::storage_usage());
+
+        // A single record the size of a bare balance entry.
+        let mut key = nested_key(&contract.storage_prefix, StorageKey::BalanceEntries);
+        key.extend(borsh::to_vec(&holders[1]).unwrap());
+        key[0] ^= 0xff;
+        let value = borsh::to_vec(&contract.balance[&holders[1]]).unwrap();
+        let initial = env::storage_usage();
+        env::storage_write(&key, &value);
+        let entry_only = env::storage_usage() - initial;
+
+        // The lookup backend writes nothing but the entry; the default one
+        // also writes the map's key list and the account index.
+        #[cfg(feature = "lookup-balances")]
+        assert_eq!(entry_only, per_holder);
+        #[cfg(not(feature = "lookup-balances"))]
+        assert!(per_holder > entry_only);
+    }
+
+    #[test]
+    fn test_approved_protocol_transfer_from() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("alice.testnet".parse().unwrap(), 100.into());
+        contract.set_approved_protocol("dex.testnet".parse().unwrap(), true);
+
+        testing_env!(get_context("dex.testnet".to_string()).build());
+        contract.transfer_from(
+            "alice.testnet".parse().unwrap(),
+            "test.testnet".parse().unwrap(),
+            60.into(),
+        );
+
+        assert_eq!(
+            60u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"60","operator_id":"dex.testnet","owner_id":"alice.testnet","receiver_id":"test.testnet"}],"event":"operator_transfer","standard":"nep141","version":"1.0.0"}"#,
+                r#"EVENT_JSON:{"data":[{"amount":"60","new_owner_id":"test.testnet","old_owner_id":"alice.testnet"}],"event":"ft_transfer","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "insufficient allowance")]
+    fn test_unapproved_protocol_transfer_from() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("alice.testnet".parse().unwrap(), 100.into());
+        contract.set_approved_protocol("dex.testnet".parse().unwrap(), true);
+        contract.set_approved_protocol("dex.testnet".parse().unwrap(), false);
+
+        testing_env!(get_context("dex.testnet".to_string()).build());
+        contract.transfer_from(
+            "alice.testnet".parse().unwrap(),
+            "test.testnet".parse().unwrap(),
+            60.into(),
+        );
+    }
+
+    #[test]
+    fn test_mint_direct() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        assert_eq!(
+            None,
+            contract.mint("test.testnet".parse().unwrap(), 10.into())
+        );
+        assert_eq!(
+            10u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_mint_requires_confirmation() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.set_mint_requires_confirmation(true);
+        let mint_id = contract
+            .mint("test.testnet".parse().unwrap(), 10.into())
+            .unwrap();
+        assert_eq!(None, contract.balance_of("test.testnet".parse().unwrap()));
+        assert_eq!(TOTAL_SUPPLY, *contract.total_supply());
+
+        contract.confirm_mint(mint_id);
+        assert_eq!(
+            10u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert!(contract.pending_mint(mint_id).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "mint requires confirmation")]
+    fn test_mint_batch_requires_direct_mode() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.set_mint_requires_confirmation(true);
+        contract.mint_batch(vec![("test.testnet".parse().unwrap(), 10.into())]);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot transfer to system account")]
+    fn test_ft_transfer_to_system_account() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer("system".parse().unwrap(), 1.into(), None, None);
+    }
+
+    #[test]
+    fn test_transfer_count() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        for _ in 0..3 {
+            contract.transfer("test.testnet".parse().unwrap(), 1.into());
+        }
+        let info = contract
+            .get_account_info("nutinaguti.testnet".parse().unwrap())
+            .unwrap();
+        assert_eq!(3, info.transfer_count);
+        assert_eq!(U128(7), info.balance);
+        assert_eq!(
+            0,
+            contract
+                .get_account_info("test.testnet".parse().unwrap())
+                .unwrap()
+                .transfer_count
+        );
+    }
+
+    #[test]
+    fn test_transfer_all() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        assert_eq!(
+            U128(10),
+            contract.transfer_all("test.testnet".parse().unwrap())
+        );
+        assert_eq!(
+            0u128,
+            *contract
+                .balance_of("nutinaguti.testnet".parse().unwrap())
+                .unwrap_or(&0)
+        );
+        assert_eq!(
+            10u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_transfer_all_keeps_lock_and_burns_fee() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 100.into());
+        contract.set_locked("test.testnet".parse().unwrap(), 40.into());
+        contract.set_burn_fee(1_000);
+
+        testing_env!(get_context("test.testnet".to_string()).build());
+        assert_eq!(
+            U128(60),
+            contract.transfer_all("other.testnet".parse().unwrap())
+        );
+        assert_eq!(
+            U128(40),
+            contract.locked_of("test.testnet".parse().unwrap())
+        );
+        assert_eq!(
+            54u128,
+            *contract
+                .balance_of("other.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_init_normalize_symbol() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let contract = ERC20::init(
+            "nutinaguti.testnet".parse().unwrap(),
+            " FUN COIN ".to_string(),
+            " func".to_string(),
+            DECIMALS,
+            TOTAL_SUPPLY.into(),
+            events::DEFAULT_EVENT_STANDARD.to_string(),
+            events::DEFAULT_EVENT_VERSION.to_string(),
+            Vec::new(),
+            true,
+            b"t",
+        );
+        assert_eq!("FUNC", contract.symbol());
+        assert_eq!("FUN COIN", contract.name());
+    }
+
+    #[test]
+    #[cfg(not(feature = "lookup-balances"))]
+    fn test_audit_supply_partial() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let contract = ERC20::init(
+            "nutinaguti.testnet".parse().unwrap(),
+            "FUN COIN".to_string(),
+            "FUNC".to_string(),
+            DECIMALS,
+            100.into(),
+            events::DEFAULT_EVENT_STANDARD.to_string(),
+            events::DEFAULT_EVENT_VERSION.to_string(),
+            vec![
+                ("a.testnet".parse().unwrap(), 50.into()),
+                ("b.testnet".parse().unwrap(), 30.into()),
+                ("c.testnet".parse().unwrap(), 20.into()),
+            ],
+            false,
+            b"t",
+        );
+
+        let (first, next_index) = contract.audit_supply_partial(0, 2);
+        assert_eq!(2, next_index);
+        let (second, next_index) = contract.audit_supply_partial(next_index, 2);
+        assert_eq!(3, next_index);
+        assert_eq!(*contract.total_supply(), first.0 + second.0);
+    }
+
+    #[test]
+    fn test_display_amount_round_trip() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        assert_eq!(
+            U128(15 * 10u128.pow(17)),
+            contract.from_display_amount("1.5".to_string())
+        );
+        assert_eq!("1.5", contract.to_display_amount(U128(15 * 10u128.pow(17))));
+        assert_eq!("2", contract.to_display_amount(U128(2 * 10u128.pow(18))));
+        assert_eq!("0.000000000000000001", contract.to_display_amount(U128(1)));
+
+        contract.decimals = 0;
+        assert_eq!(U128(100), contract.from_display_amount("100".to_string()));
+        assert_eq!("100", contract.to_display_amount(U128(100)));
+        assert!(std::panic::catch_unwind(|| parse_display_amount("1.5", 0)).is_err());
+        assert!(std::panic::catch_unwind(|| parse_display_amount("100.", 0)).is_err());
+    }
+
+    #[test]
+    fn test_ft_transfer_call_correlation_id() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        testing_env!(context.attached_deposit(1).build());
+        drop(contract.ft_transfer_call(
+            "receiver.testnet".parse().unwrap(),
+            10.into(),
+            None,
+            "hello".to_string(),
+            Some("trace-1".to_string()),
+        ));
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"10","correlation_id":"trace-1","new_owner_id":"receiver.testnet","old_owner_id":"nutinaguti.testnet"}],"event":"ft_transfer","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+        let receipts = get_created_receipts();
+        assert!(matches!(
+            &receipts[0].actions[0],
+            VmAction::FunctionCall { function_name, .. } if function_name == "ft_on_transfer"
+        ));
+        assert!(matches!(
+            &receipts[1].actions[0],
+            VmAction::FunctionCall { function_name, .. } if function_name == "ft_resolve_transfer"
+        ));
+
+        testing_env!(
+            get_context("erc20.testnet".to_string()).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(b"\"4\"".to_vec())],
+        );
+        let used = contract.ft_resolve_transfer(
+            "nutinaguti.testnet".parse().unwrap(),
+            "receiver.testnet".parse().unwrap(),
+            10.into(),
+            Some("trace-1".to_string()),
+        );
+        assert_eq!(U128(6), used);
+        assert_eq!(
+            4u128,
+            *contract
+                .balance_of("nutinaguti.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"correlation_id":"trace-1","receiver_id":"receiver.testnet","refunded_amount":"4","sender_id":"nutinaguti.testnet","used_amount":"6"}],"event":"ft_resolve_transfer","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    fn test_ft_transfer_call_resolves_to_used_amount() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        testing_env!(context.attached_deposit(1).build());
+        let result: PromiseOrValue<U128> = contract.ft_transfer_call(
+            "receiver.testnet".parse().unwrap(),
+            10.into(),
+            None,
+            "hello".to_string(),
+            None,
+        );
+        assert!(matches!(result, PromiseOrValue::Promise(_)));
+
+        testing_env!(
+            get_context("erc20.testnet".to_string()).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(b"\"0\"".to_vec())],
+        );
+        let used = contract.ft_resolve_transfer(
+            "nutinaguti.testnet".parse().unwrap(),
+            "receiver.testnet".parse().unwrap(),
+            10.into(),
+            None,
+        );
+        assert_eq!(U128(10), used);
+        assert_eq!(
+            10u128,
+            *contract
+                .balance_of("receiver.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "burn below min supply")]
+    fn test_burn_stops_at_min_supply() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 100.into());
+        contract.set_min_supply((TOTAL_SUPPLY + 60).into());
+        contract.burn("test.testnet".parse().unwrap(), 40.into());
+        assert_eq!(TOTAL_SUPPLY + 60, *contract.total_supply());
+        contract.burn("test.testnet".parse().unwrap(), 1.into());
+    }
+
+    #[test]
+    fn test_burn_fee_skipped_at_min_supply() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.set_burn_fee(1_000);
+        contract.set_min_supply((TOTAL_SUPPLY + 100).into());
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        contract.transfer("test.testnet".parse().unwrap(), 50.into());
+
+        assert_eq!(TOTAL_SUPPLY + 100, *contract.total_supply());
+        assert_eq!(
+            50u128,
+            *contract
+                .balance_of("test.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"5","owner_id":"nutinaguti.testnet"}],"event":"burn_skipped","standard":"nep141","version":"1.0.0"}"#,
+                r#"EVENT_JSON:{"data":[{"amount":"50","new_owner_id":"test.testnet","old_owner_id":"nutinaguti.testnet"}],"event":"ft_transfer","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    fn test_decimals_value() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let contract = init_contract();
+        let decimals: u8 = contract.decimals_value();
+        assert_eq!(DECIMALS, decimals);
+        assert_eq!(DECIMALS, contract.ft_metadata().decimals);
+    }
+
+    #[test]
+    fn test_transfer_with_tip() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        contract.transfer_with_tip(
+            "shop.testnet".parse().unwrap(),
+            90.into(),
+            "creator.testnet".parse().unwrap(),
+            10.into(),
+        );
+
+        assert_eq!(
+            90u128,
+            *contract
+                .balance_of("shop.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            10u128,
+            *contract
+                .balance_of("creator.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"90","new_owner_id":"shop.testnet","old_owner_id":"nutinaguti.testnet"}],"event":"ft_transfer","standard":"nep141","version":"1.0.0"}"#,
+                r#"EVENT_JSON:{"data":[{"amount":"10","new_owner_id":"creator.testnet","old_owner_id":"nutinaguti.testnet"}],"event":"ft_transfer","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "insufficient balance")]
+    fn test_transfer_with_tip_insufficient_combined() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.transfer_with_tip(
+            "shop.testnet".parse().unwrap(),
+            95.into(),
+            "creator.testnet".parse().unwrap(),
+            10.into(),
+        );
+    }
+
+    #[test]
+    fn test_is_allowance_expired() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.block_timestamp(100).build());
+        let mut contract = init_contract();
+        contract.approve_until("live.testnet".parse().unwrap(), 10.into(), 200);
+        contract.approve_until("stale.testnet".parse().unwrap(), 10.into(), 50);
+
+        let owner: AccountId = "nutinaguti.testnet".parse().unwrap();
+        assert!(!contract.is_allowance_expired(owner.clone(), "live.testnet".parse().unwrap()));
+        assert!(contract.is_allowance_expired(owner.clone(), "stale.testnet".parse().unwrap()));
+        assert!(contract.is_allowance_expired(owner, "none.testnet".parse().unwrap()));
+    }
+
+    fn setup_receiver_allowlist() -> ERC20 {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        contract.set_receiver_allowlist_enabled(true);
+        contract.set_allowed_receiver("dex.testnet".parse().unwrap(), true);
+        testing_env!(context.attached_deposit(1).build());
+        contract
+    }
+
+    #[test]
+    fn test_ft_transfer_call_allowlisted_receiver() {
+        let mut contract = setup_receiver_allowlist();
+        drop(contract.ft_transfer_call(
+            "dex.testnet".parse().unwrap(),
+            10.into(),
+            None,
+            String::new(),
+            None,
+        ));
+        assert_eq!(
+            10u128,
+            *contract.balance_of("dex.testnet".parse().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "receiver contract not allowlisted")]
+    fn test_ft_transfer_call_unlisted_receiver() {
+        let mut contract = setup_receiver_allowlist();
+        drop(contract.ft_transfer_call(
+            "other.testnet".parse().unwrap(),
+            10.into(),
+            None,
+            String::new(),
+            None,
+        ));
+    }
+
+    #[test]
+    fn test_transfer_to_burn_address() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        contract.set_burn_address(Some("burn.testnet".parse().unwrap()));
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        contract.transfer("burn.testnet".parse().unwrap(), 4.into());
+
+        assert_eq!(TOTAL_SUPPLY + 6, *contract.total_supply());
+        assert_eq!(None, contract.balance_of("burn.testnet".parse().unwrap()));
+        assert_eq!(
+            6u128,
+            *contract
+                .balance_of("nutinaguti.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"4","owner_id":"nutinaguti.testnet"}],"event":"ft_burn","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    fn test_supported_standards() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let contract = init_contract();
+        assert_eq!(
+            vec!["nep141", "nep145", "nep148", "nep297"],
+            contract.supported_standards()
+        );
+    }
+
+    fn setup_clawback() -> (ERC20, VMContextBuilder) {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = init_contract();
+        contract.set_clawback_window(60);
+        contract.mint("buyer.testnet".parse().unwrap(), 10.into());
+        (contract, context)
+    }
+
+    #[test]
+    fn test_clawback_within_window() {
+        let (mut contract, mut context) = setup_clawback();
+        assert_eq!(U128(10), contract.mint_record(0).unwrap().amount);
+        testing_env!(context.block_timestamp(59 * NANOS_PER_SEC).build());
+        contract.clawback("buyer.testnet".parse().unwrap(), 0);
+
+        assert_eq!(
+            0u128,
+            *contract
+                .balance_of("buyer.testnet".parse().unwrap())
+                .unwrap()
+        );
+        assert_eq!(TOTAL_SUPPLY, *contract.total_supply());
+        assert_eq!(10, contract.total_burned);
+        assert!(contract.mint_record(0).is_none());
+        let history = contract.account_history("buyer.testnet".parse().unwrap(), 1);
+        assert_eq!(account_history::OpType::Burn, history[0].op_type);
+        assert_eq!(U128(10), history[0].amount);
+    }
+
+    #[test]
+    #[should_panic(expected = "burn below min supply")]
+    fn test_clawback_below_min_supply() {
+        let (mut contract, _) = setup_clawback();
+        contract.set_min_supply((TOTAL_SUPPLY + 5).into());
+        contract.clawback("buyer.testnet".parse().unwrap(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "clawback window expired")]
+    fn test_clawback_after_window() {
+        let (mut contract, mut context) = setup_clawback();
+        testing_env!(context.block_timestamp(60 * NANOS_PER_SEC).build());
+        contract.clawback("buyer.testnet".parse().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_internal_credit_holder_count() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.internal_credit(&"a.testnet".parse().unwrap(), 10);
+        assert_eq!(1, contract.holder_count());
+        contract.internal_credit(&"a.testnet".parse().unwrap(), 5);
+        assert_eq!(1, contract.holder_count());
+
+        testing_env!(get_context("a.testnet".to_string()).build());
+        contract.transfer("b.testnet".parse().unwrap(), 15.into());
+        assert_eq!(1, contract.holder_count());
+        testing_env!(get_context("b.testnet".to_string()).build());
+        contract.burn("b.testnet".parse().unwrap(), 15.into());
+        assert_eq!(0, contract.holder_count());
+    }
+
+    #[test]
+    fn test_recover_self_balance() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint(env::current_account_id(), 10.into());
+
+        assert_eq!(
+            U128(10),
+            contract.recover_self_balance("rescue.testnet".parse().unwrap())
+        );
+        assert_eq!(Some(&0), contract.balance_of(env::current_account_id()));
+        assert_eq!(
+            10u128,
+            *contract
+                .balance_of("rescue.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    fn setup_periodic_allowance() -> (ERC20, VMContextBuilder) {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 100.into());
+        contract.approve_periodic("service.testnet".parse().unwrap(), 10.into(), 30);
+        testing_env!(context
+            .predecessor_account_id("service.testnet".parse().unwrap())
+            .build());
+        (contract, context)
+    }
+
+    fn pull(contract: &mut ERC20, value: u128) {
+        contract.transfer_from(
+            "nutinaguti.testnet".parse().unwrap(),
+            "service.testnet".parse().unwrap(),
+            value.into(),
+        );
+    }
+
+    #[test]
+    fn test_periodic_allowance_within_period() {
+        let (mut contract, _) = setup_periodic_allowance();
+        pull(&mut contract, 4);
+        pull(&mut contract, 6);
+        let allowance = contract
+            .periodic_allowance(
+                "nutinaguti.testnet".parse().unwrap(),
+                "service.testnet".parse().unwrap(),
+            )
+            .unwrap();
+        assert_eq!(U128(10), allowance.spent_this_period);
+    }
+
+    #[test]
+    #[should_panic(expected = "periodic allowance exhausted")]
+    fn test_periodic_allowance_exhausted() {
+        let (mut contract, mut context) = setup_periodic_allowance();
+        pull(&mut contract, 10);
+        testing_env!(context.block_timestamp(29 * NANOS_PER_SEC).build());
+        pull(&mut contract, 1);
+    }
+
+    #[test]
+    fn test_periodic_allowance_resets() {
+        let (mut contract, mut context) = setup_periodic_allowance();
+        pull(&mut contract, 10);
+        testing_env!(context.block_timestamp(65 * NANOS_PER_SEC).build());
+        pull(&mut contract, 10);
+
+        let allowance = contract
+            .periodic_allowance(
+                "nutinaguti.testnet".parse().unwrap(),
+                "service.testnet".parse().unwrap(),
+            )
+            .unwrap();
+        assert_eq!(60 * NANOS_PER_SEC, allowance.period_start);
+        assert_eq!(
+            20u128,
+            *contract
+                .balance_of("service.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_verify_reference() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        assert!(!contract.verify_reference(b"{}".to_vec()));
+
+        contract.set_reference(
+            Some("https://example.com/func.json".to_string()),
+            Some(env::sha256(b"{}").into()),
+        );
+        assert!(contract.verify_reference(b"{}".to_vec()));
+        assert!(!contract.verify_reference(b"{ }".to_vec()));
+        assert_eq!(
+            Some("https://example.com/func.json".to_string()),
+            contract.ft_metadata().reference
+        );
+    }
+
+    #[test]
+    fn test_storage_balance_bounds_json() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let contract = init_contract();
+        let bounds = contract.storage_balance_bounds();
+        let json = serde_json::to_value(&bounds).unwrap();
+        let keys: Vec<&String> = json.as_object().unwrap().keys().collect();
+        assert_eq!(vec!["max", "min"], keys);
+        assert_eq!(
+            serde_json::json!({ "min": bounds.min, "max": bounds.max }),
+            json
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "account is frozen")]
+    fn test_frozen_transfer_to_burn_address() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 100.into());
+        contract.set_burn_address(Some("burn.testnet".parse().unwrap()));
+        contract.freeze("test.testnet".parse().unwrap());
+        testing_env!(get_context("test.testnet".to_string()).build());
+        contract.transfer("burn.testnet".parse().unwrap(), 50.into());
+    }
+
+    #[test]
+    fn test_emergency_withdraw_to_burn_address() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 100.into());
+        contract.set_burn_address(Some("burn.testnet".parse().unwrap()));
+        testing_env!(get_context("test.testnet".to_string()).build());
+        contract.emergency_withdraw("burn.testnet".parse().unwrap());
+        assert_eq!(TOTAL_SUPPLY, *contract.total_supply());
+        assert_eq!(None, contract.balance_of("burn.testnet".parse().unwrap()));
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"100","owner_id":"test.testnet"}],"event":"ft_burn","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    fn test_emergency_withdraw_emits_ft_transfer() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 100.into());
+        testing_env!(get_context("test.testnet".to_string()).build());
+        contract.emergency_withdraw("safe.testnet".parse().unwrap());
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"100","new_owner_id":"safe.testnet","old_owner_id":"test.testnet"}],"event":"ft_transfer","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "receiver not allowed")]
+    fn test_execute_recovery_to_denylisted() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = init_contract();
+        contract.mint("lost.testnet".parse().unwrap(), 10.into());
+        contract.set_denylisted("bad.testnet".parse().unwrap(), true);
+        let request_id = contract.request_recovery(
+            "lost.testnet".parse().unwrap(),
+            "bad.testnet".parse().unwrap(),
+        );
+        testing_env!(context
+            .block_timestamp(recovery::RECOVERY_DELAY_SECS * NANOS_PER_SEC)
+            .build());
+        contract.execute_recovery(request_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot transfer to token contract")]
+    fn test_emergency_withdraw_to_token_contract() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 100.into());
+        testing_env!(get_context("test.testnet".to_string()).build());
+        contract.emergency_withdraw(env::current_account_id());
+    }
+
+    #[test]
+    #[should_panic(expected = "burn amount must be positive")]
+    fn test_burn_zero() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.burn("nutinaguti.testnet".parse().unwrap(), 0.into());
+    }
+
+    fn setup_min_balance_with_fee() -> ERC20 {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 1000.into());
+        contract.set_min_balance(100.into());
+        contract.set_burn_fee(100);
+        testing_env!(get_context("test.testnet".to_string()).build());
+        contract
+    }
+
+    #[test]
+    #[should_panic(expected = "balance below minimum")]
+    fn test_min_balance_counts_burn_fee() {
+        let mut contract = setup_min_balance_with_fee();
+        contract.transfer("other.testnet".parse().unwrap(), 905.into());
+    }
+
+    #[test]
+    fn test_min_balance_full_transfer_with_fee() {
+        let mut contract = setup_min_balance_with_fee();
+        contract.transfer("other.testnet".parse().unwrap(), 1000.into());
+        assert_eq!(None, contract.balance_of("test.testnet".parse().unwrap()));
+        assert_eq!(
+            990u128,
+            *contract
+                .balance_of("other.testnet".parse().unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_min_balance_cleanup_refunds_storage_deposit() {
+        let mut context = get_context("test.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract();
+        let deposit = contract.storage_balance_bounds().min.0;
+        testing_env!(context.attached_deposit(deposit).build());
+        contract.storage_deposit(None, None);
+
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        contract.mint("test.testnet".parse().unwrap(), 1000.into());
+        contract.set_min_balance(100.into());
+        testing_env!(get_context("test.testnet".to_string()).build());
+        contract.transfer("other.testnet".parse().unwrap(), 1000.into());
+
+        assert_eq!(
+            None,
+            contract.storage_balance_of("test.testnet".parse().unwrap())
+        );
+        let receipts = get_created_receipts();
+        assert_eq!("test.testnet", receipts[0].receiver_id.as_str());
+        assert_eq!(vec![VmAction::Transfer { deposit }], receipts[0].actions);
+    }
+
+    #[test]
+    fn test_transfer_count_survives_min_balance_cleanup() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 10.into());
+        contract.set_min_balance(5.into());
+        testing_env!(get_context("test.testnet".to_string()).build());
+        contract.transfer("other.testnet".parse().unwrap(), 10.into());
+        assert_eq!(
+            None,
+            contract.get_account_info("test.testnet".parse().unwrap())
+        );
+        assert_eq!(
+            1,
+            contract.transfer_count_of("test.testnet".parse().unwrap())
+        );
+
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        contract.mint("test.testnet".parse().unwrap(), 10.into());
+        testing_env!(get_context("test.testnet".to_string()).build());
+        contract.transfer("other.testnet".parse().unwrap(), 5.into());
+        assert_eq!(
+            2,
+            contract
+                .get_account_info("test.testnet".parse().unwrap())
+                .unwrap()
+                .transfer_count
+        );
+    }
+
+    #[test]
+    fn test_transfer_count_burn_address() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.set_burn_address(Some("burn.testnet".parse().unwrap()));
+        contract.mint("test.testnet".parse().unwrap(), 10.into());
+        testing_env!(get_context("test.testnet".to_string()).build());
+        contract.transfer("burn.testnet".parse().unwrap(), 4.into());
+        assert_eq!(
+            1,
+            contract.transfer_count_of("test.testnet".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_can_transfer_soulbound() {
+        let mut contract = setup_can_transfer();
+        contract.set_transferable(false);
+        assert!(!can_transfer(&contract, 1));
+        assert_eq!(
+            U128(0),
+            contract.spendable_balance("nutinaguti.testnet".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_can_transfer_denylisted() {
+        let mut contract = setup_can_transfer();
+        contract.set_denylisted("test.testnet".parse().unwrap(), true);
+        assert!(!can_transfer(&contract, 1));
+    }
+
+    #[test]
+    fn test_can_transfer_to_token_contract() {
+        let contract = setup_can_transfer();
+        assert!(!contract.can_transfer(
+            "nutinaguti.testnet".parse().unwrap(),
+            env::current_account_id(),
+            1.into(),
+        ));
+    }
+
+    #[test]
+    fn test_can_transfer_min_balance() {
+        let mut contract = setup_can_transfer();
+        contract.set_min_balance(10.into());
+        assert!(can_transfer(&contract, 100));
+        assert!(can_transfer(&contract, 90));
+        assert!(!can_transfer(&contract, 95));
+    }
+
+    #[test]
+    fn test_can_transfer_unregistered_receiver() {
+        let mut contract = setup_can_transfer();
+        contract.set_registration_policy(false, false);
+        assert!(!can_transfer(&contract, 1));
+    }
+
+    #[test]
+    fn test_transfer_emits_ft_transfer() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        contract.transfer("test.testnet".parse().unwrap(), 4.into());
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"4","new_owner_id":"test.testnet","old_owner_id":"nutinaguti.testnet"}],"event":"ft_transfer","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    fn test_ft_transfer_emits_ft_transfer() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer("test.testnet".parse().unwrap(), 4.into(), None, None);
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"4","new_owner_id":"test.testnet","old_owner_id":"nutinaguti.testnet"}],"event":"ft_transfer","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "allowance exceeds cap")]
+    fn test_approve_periodic_max_allowance() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.set_max_allowance(Some(5.into()));
+        contract.approve_periodic("service.testnet".parse().unwrap(), 10.into(), 30);
+    }
+
+    #[test]
+    #[should_panic(expected = "too many spenders")]
+    fn test_approve_periodic_max_spenders() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.set_max_spenders_per_owner(Some(2));
+        contract.approve("a.testnet".parse().unwrap(), 1.into());
+        contract.approve_periodic("b.testnet".parse().unwrap(), 1.into(), 30);
+        contract.approve_periodic("b.testnet".parse().unwrap(), 2.into(), 30);
+        contract.approve("c.testnet".parse().unwrap(), 1.into());
+    }
+
+    #[test]
+    fn test_revoke_all_clears_periodic_allowances() {
+        let (mut contract, mut context) = setup_periodic_allowance();
+        testing_env!(context
+            .predecessor_account_id("nutinaguti.testnet".parse().unwrap())
+            .build());
+        contract.revoke_all();
+        assert_eq!(
+            None,
+            contract.periodic_allowance(
+                "nutinaguti.testnet".parse().unwrap(),
+                "service.testnet".parse().unwrap(),
+            )
+        );
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"owner_id":"nutinaguti.testnet","spender_ids":["service.testnet"]}],"event":"allowances_revoked","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    fn test_execute_recovery_moves_lock() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = init_contract();
+        contract.mint("lost.testnet".parse().unwrap(), 10.into());
+        contract.set_locked("lost.testnet".parse().unwrap(), 4.into());
+        let request_id = contract.request_recovery(
+            "lost.testnet".parse().unwrap(),
+            "new.testnet".parse().unwrap(),
+        );
+
+        testing_env!(context
+            .block_timestamp(recovery::RECOVERY_DELAY_SECS * NANOS_PER_SEC)
+            .build());
+        assert_eq!(U128(10), contract.execute_recovery(request_id));
+        assert_eq!(
+            10u128,
+            *contract.balance_of("new.testnet".parse().unwrap()).unwrap()
+        );
+        assert_eq!(U128(4), contract.locked_of("new.testnet".parse().unwrap()));
+        assert_eq!(U128(0), contract.locked_of("lost.testnet".parse().unwrap()));
+    }
+
+    const ONE_TOKEN: u128 = 1_000_000_000_000_000_000;
+
+    fn setup_redenomination() -> ERC20 {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract_with_supply(0);
+        contract.mint("a.testnet".parse().unwrap(), (10 * ONE_TOKEN).into());
+        contract.set_locked("a.testnet".parse().unwrap(), (2 * ONE_TOKEN).into());
+        contract.set_min_balance(ONE_TOKEN.into());
+        contract.set_max_allowance(Some((100 * ONE_TOKEN).into()));
+        contract.set_burn_fee_bounds(ONE_TOKEN.into(), Some((5 * ONE_TOKEN).into()));
+        contract.faucet_amount = 3 * ONE_TOKEN;
+        contract.referral_pool = 7 * ONE_TOKEN;
+        contract.set_clawback_window(60);
+        contract.mint("b.testnet".parse().unwrap(), (4 * ONE_TOKEN).into());
+        contract.set_mint_requires_confirmation(true);
+        contract.mint("c.testnet".parse().unwrap(), (6 * ONE_TOKEN).into());
+
+        testing_env!(context
+            .predecessor_account_id("a.testnet".parse().unwrap())
+            .build());
+        contract.approve("spender.testnet".parse().unwrap(), (8 * ONE_TOKEN).into());
+        testing_env!(context
+            .predecessor_account_id("buyer.testnet".parse().unwrap())
+            .attached_deposit(1)
+            .build());
+        contract.reserve((9 * ONE_TOKEN).into());
+        testing_env!(context
+            .predecessor_account_id("nutinaguti.testnet".parse().unwrap())
+            .attached_deposit(0)
+            .build());
+        contract
+    }
+
+    fn assert_redenominated(contract: &ERC20) {
+        assert_eq!(6, contract.decimals);
+        assert_eq!(
+            10_000_000u128,
+            *contract.balance_of("a.testnet".parse().unwrap()).unwrap()
+        );
+        assert_eq!(
+            U128(2_000_000),
+            contract.locked_of("a.testnet".parse().unwrap())
+        );
+        assert_eq!(
+            8_000_000u128,
+            *contract.allowance(
+                "a.testnet".parse().unwrap(),
+                "spender.testnet".parse().unwrap()
+            )
+        );
+        assert_eq!(
+            9_000_000,
+            contract
+                .reservation_of("buyer.testnet".parse().unwrap())
+                .unwrap()
+                .amount
+        );
+        assert_eq!(U128(6_000_000), contract.pending_mint(0).unwrap().value);
+        assert_eq!(U128(4_000_000), contract.mint_record(0).unwrap().amount);
+        assert_eq!(1_000_000, contract.min_balance);
+        assert_eq!(Some(100_000_000), contract.max_allowance);
+        assert_eq!(1_000_000, contract.min_fee);
+        assert_eq!(Some(5_000_000), contract.max_fee);
+        assert_eq!(3_000_000, contract.faucet_amount);
+        assert_eq!(7_000_000, contract.referral_pool);
+        assert_eq!(14_000_000, contract.total_supply);
+        assert!(contract.redenomination().is_none());
+        assert!(!contract.migrating);
+    }
+
+    #[test]
+    #[cfg(not(feature = "lookup-balances"))]
+    fn test_redenominate_in_batches() {
+        let mut contract = setup_redenomination();
+        contract.redenominate(6);
+        let mut batches = 1;
+        while !contract.redenominate_batch(1) {
+            batches += 1;
+        }
+        // a, b and buyer in the account index, one pending mint, one record.
+        assert_eq!(5, batches);
+        assert_redenominated(&contract);
+    }
+
+    #[test]
+    #[cfg(feature = "lookup-balances")]
+    fn test_redenominate_accounts() {
+        let mut contract = setup_redenomination();
+        contract.redenominate(6);
+        assert!(!contract.redenominate_batch(10));
+        assert!(!contract.redenominate_accounts(vec!["buyer.testnet".parse().unwrap()]));
+        assert!(!contract.redenominate_accounts(vec!["a.testnet".parse().unwrap()]));
+        assert!(contract.redenominate_accounts(vec![
+            "a.testnet".parse().unwrap(),
+            "b.testnet".parse().unwrap(),
+        ]));
+        assert_redenominated(&contract);
+    }
+
+    #[test]
+    #[should_panic(expected = "contract is migrating")]
+    fn test_transfer_during_redenomination() {
+        let mut contract = setup_redenomination();
+        contract.redenominate(6);
+        contract.transfer("b.testnet".parse().unwrap(), 1.into());
+    }
+
+    #[test]
+    #[should_panic(expected = "wrapped tokens outstanding")]
+    fn test_redenominate_with_wrapped_supply() {
+        let mut contract = setup_redenomination();
+        contract.wrapped_supply = 1;
+        contract.redenominate(6);
+    }
+
+    fn setup_withdraw_near_with_liabilities() -> ERC20 {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract();
+        testing_env!(context
+            .predecessor_account_id("buyer.testnet".parse().unwrap())
+            .attached_deposit(3)
+            .build());
+        contract.reserve(1.into());
+        contract.wrapped_supply = 4;
+        contract.total_storage_deposits = 5;
+        let reserve = 1_000 * env::storage_byte_cost();
+        testing_env!(context
+            .predecessor_account_id("nutinaguti.testnet".parse().unwrap())
+            .attached_deposit(0)
+            .storage_usage(1_000)
+            .account_balance(reserve + 12 + 10)
+            .build());
+        contract
+    }
+
+    #[test]
+    fn test_withdraw_near_above_liabilities() {
+        let mut contract = setup_withdraw_near_with_liabilities();
+        assert_eq!(U128(12), contract.near_liabilities());
+        drop(contract.withdraw_near("treasury.testnet".parse().unwrap(), 10.into()));
+        assert_eq!(
+            vec![VmAction::Transfer { deposit: 10 }],
+            get_created_receipts()[0].actions
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "withdrawal would breach liabilities")]
+    fn test_withdraw_near_below_liabilities() {
+        let mut contract = setup_withdraw_near_with_liabilities();
+        drop(contract.withdraw_near("treasury.testnet".parse().unwrap(), 11.into()));
+    }
+
+    #[test]
+    fn test_storage_deposits_tracked() {
+        let (mut contract, min) = setup_storage_deposit(ONE_NEAR);
+        assert_eq!(min, contract.total_storage_deposits);
+        testing_env!(get_context("test.testnet".to_string())
+            .attached_deposit(ONE_NEAR)
+            .build());
+        contract.storage_deposit(None, None);
+        assert_eq!(min, contract.total_storage_deposits);
+    }
+
+    #[test]
+    fn test_ft_balance_of_and_total_supply() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 10.into());
+        assert_eq!(
+            U128(10),
+            contract.ft_balance_of("test.testnet".parse().unwrap())
+        );
+        assert_eq!(
+            U128(0),
+            contract.ft_balance_of("other.testnet".parse().unwrap())
+        );
+        assert_eq!(U128(TOTAL_SUPPLY + 10), contract.ft_total_supply());
+    }
+
+    #[test]
+    fn test_storage_unregister_not_registered() {
+        testing_env!(get_context("test.testnet".to_string())
+            .attached_deposit(1)
+            .build());
+        let mut contract = init_contract();
+        assert!(!contract.storage_unregister(None));
+    }
+
+    #[test]
+    fn test_storage_unregister_refunds_deposit() {
+        let (mut contract, min) = setup_storage_deposit(0);
+        assert!(contract.storage_unregister(None));
+        assert_eq!(
+            None,
+            contract.storage_balance_of("test.testnet".parse().unwrap())
+        );
+        assert_eq!(None, contract.balance_of("test.testnet".parse().unwrap()));
+        assert_eq!(0, contract.total_storage_deposits);
+        assert_eq!(
+            vec![VmAction::Transfer { deposit: min }],
+            get_created_receipts()[0].actions
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot unregister with a positive balance")]
+    fn test_storage_unregister_with_balance() {
+        let (mut contract, _) = setup_storage_deposit(0);
+        contract.internal_credit(&"test.testnet".parse().unwrap(), 10);
+        contract.storage_unregister(None);
+    }
+
+    #[test]
+    fn test_storage_unregister_force_burns_balance() {
+        let (mut contract, _) = setup_storage_deposit(0);
+        contract.internal_credit(&"test.testnet".parse().unwrap(), 10);
+        contract.total_supply += 10;
+        assert!(contract.storage_unregister(Some(true)));
+        assert_eq!(None, contract.balance_of("test.testnet".parse().unwrap()));
+        assert_eq!(TOTAL_SUPPLY, contract.total_supply);
+        assert_eq!(10, contract.total_burned);
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"10","owner_id":"test.testnet"}],"event":"ft_burn","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "contract is migrating")]
+    fn test_accrue_emission_while_migrating() {
+        let owner = "nutinaguti.testnet".parse().unwrap();
+        let mut context = get_context(owner);
+        testing_env!(context.build());
+        let mut contract = init_contract();
+        contract.configure_emission(5.into(), "treasury.testnet".parse().unwrap());
+        contract.begin_migration();
+
+        testing_env!(context.block_timestamp(10_000_000_000).build());
+        contract.accrue_emission();
+    }
+
+    #[test]
+    fn test_burn_entrypoints_emit_ft_burn() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("a.testnet".parse().unwrap(), 10.into());
+        contract.mint("b.testnet".parse().unwrap(), 10.into());
+
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        contract.batch_burn(vec![
+            ("a.testnet".parse().unwrap(), 1.into()),
+            ("b.testnet".parse().unwrap(), 2.into()),
+        ]);
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"1","owner_id":"a.testnet"},{"amount":"2","owner_id":"b.testnet"}],"event":"ft_burn","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+
+        testing_env!(get_context("a.testnet".to_string()).build());
+        contract.burn("a.testnet".parse().unwrap(), 4.into());
+        contract.burn_all("a.testnet".parse().unwrap());
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"4","owner_id":"a.testnet"}],"event":"ft_burn","standard":"nep141","version":"1.0.0"}"#,
+                r#"EVENT_JSON:{"data":[{"amount":"5","owner_id":"a.testnet"}],"event":"ft_burn","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "only account or owner")]
+    fn test_burn_unauthorized() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("test.testnet".parse().unwrap(), 100.into());
+
+        testing_env!(get_context("other.testnet".to_string()).build());
+        contract.burn("test.testnet".parse().unwrap(), 100.into());
+    }
+
+    #[test]
+    fn test_mint_entrypoints_emit_ft_mint() {
+        let mut context = get_context("nutinaguti.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract();
+        contract.mint("a.testnet".parse().unwrap(), 10.into());
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"10","owner_id":"a.testnet"}],"event":"ft_mint","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+
+        testing_env!(context.attached_deposit(1000).build());
+        contract.near_deposit();
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"1000","owner_id":"nutinaguti.testnet"}],"event":"ft_mint","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    fn test_batch_transfer_to_burn_address() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract();
+        contract.mint("nutinaguti.testnet".parse().unwrap(), 10.into());
+        contract.set_burn_address(Some("burn.testnet".parse().unwrap()));
+        contract.set_batch_events(true);
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        contract.batch_transfer(vec![
+            ("burn.testnet".parse().unwrap(), 3.into()),
+            ("test.testnet".parse().unwrap(), 2.into()),
+        ]);
+
+        assert_eq!(TOTAL_SUPPLY + 7, *contract.total_supply());
+        assert_eq!(
+            vec![
+                r#"EVENT_JSON:{"data":[{"amount":"3","owner_id":"nutinaguti.testnet"}],"event":"ft_burn","standard":"nep141","version":"1.0.0"}"#,
+                r#"EVENT_JSON:{"data":[{"amount":"2","new_owner_id":"test.testnet","old_owner_id":"nutinaguti.testnet"}],"event":"ft_transfer","standard":"nep141","version":"1.0.0"}"#
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    fn test_clawback_releases_wrapped_near() {
+        let mut context = get_context("buyer.testnet".to_string());
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = init_contract();
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        contract.set_clawback_window(60);
+        testing_env!(context.attached_deposit(ONE_NEAR).build());
+        contract.near_deposit();
+
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        contract.clawback("buyer.testnet".parse().unwrap(), 0);
+        assert_eq!(U128(0), contract.wrapped_supply());
+        assert_eq!(
+            U128(0),
+            contract.wrapped_balance_of("buyer.testnet".parse().unwrap())
+        );
+        assert_eq!(U128(0), contract.near_liabilities());
+    }
+
+    #[test]
+    fn test_debits_clamp_wrapped_balance() {
+        let mut context = get_context("buyer.testnet".to_string());
+        testing_env!(context.build());
+        let mut contract = init_contract();
+        testing_env!(context.attached_deposit(100).build());
+        contract.near_deposit();
+
+        testing_env!(context.attached_deposit(0).build());
+        contract.transfer("test.testnet".parse().unwrap(), 30.into());
+        assert_eq!(
+            U128(70),
+            contract.wrapped_balance_of("buyer.testnet".parse().unwrap())
+        );
+        assert_eq!(U128(70), contract.wrapped_supply());
+
+        contract.burn("buyer.testnet".parse().unwrap(), 20.into());
+        assert_eq!(U128(50), contract.wrapped_supply());
+
+        contract.burn_all("buyer.testnet".parse().unwrap());
+        assert_eq!(U128(0), contract.wrapped_supply());
+        assert_eq!(U128(0), contract.near_liabilities());
+    }
+
+    #[test]
+    fn test_redenominate_total_matches_truncated_balances() {
+        testing_env!(get_context("nutinaguti.testnet".to_string()).build());
+        let mut contract = init_contract_with_supply(0);
+        let holders: Vec<AccountId> = ["a.testnet", "b.testnet", "c.testnet"]
+            .iter()
+            .map(|account_id| account_id.parse().unwrap())
+            .collect();
+        for account_id in &holders {
+            contract.mint(account_id.clone(), 1_500_000_000_000u128.into());
+        }
+        contract.redenominate(6);
+        assert_eq!(4, contract.total_supply);
+
+        #[cfg(not(feature = "lookup-balances"))]
+        assert!(contract.redenominate_batch(100));
+        #[cfg(feature = "lookup-balances")]
+        assert!(contract.redenominate_accounts(holders.clone()));
+        for account_id in holders {
+            assert_eq!(1u128, *contract.balance_of(account_id).unwrap());
+        }
+        assert_eq!(3, contract.total_supply);
     }
 }
@@ -0,0 +1,55 @@
+use crate::ERC20;
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::U128,
+    require,
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingMint {
+    pub to: AccountId,
+    pub value: U128,
+}
+
+impl ERC20 {
+    /// While enabled, `mint` only queues the mint until the owner calls
+    /// `confirm_mint`; `mint_batch` and `mint_call` are rejected.
+    pub fn set_mint_requires_confirmation(&mut self, enabled: bool) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.mint_requires_confirmation = enabled;
+    }
+
+    pub(crate) fn assert_direct_mint(&self) {
+        require!(
+            !self.mint_requires_confirmation,
+            "mint requires confirmation"
+        );
+    }
+
+    pub(crate) fn internal_queue_mint(&mut self, to: AccountId, value: U128) -> u64 {
+        let mint_id = self.next_pending_mint_id;
+        self.next_pending_mint_id += 1;
+        self.pending_mints
+            .insert(mint_id, PendingMint { to, value });
+        mint_id
+    }
+
+    /// Supply caps and the minting pause apply at confirmation time.
+    pub fn confirm_mint(&mut self, mint_id: u64) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        let PendingMint { to, value } = self
+            .pending_mints
+            .remove(&mint_id)
+            .expect("no pending mint");
+        self.internal_mint(to, value);
+    }
+
+    pub fn pending_mint(&self, mint_id: u64) -> Option<&PendingMint> {
+        self.pending_mints.get(&mint_id)
+    }
+}
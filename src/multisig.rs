@@ -0,0 +1,77 @@
+use crate::ERC20;
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env::predecessor_account_id,
+    json_types::U128,
+    require,
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MintProposal {
+    pub to: AccountId,
+    pub value: U128,
+    pub approvals: Vec<AccountId>,
+}
+
+impl ERC20 {
+    /// Mints above `threshold` must go through `propose_mint` and collect
+    /// `approvals_required` minter approvals; `None` disables the check.
+    pub fn configure_mint_multisig(&mut self, threshold: Option<U128>, approvals_required: u32) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        require!(
+            approvals_required > 0,
+            "approvals required must be positive"
+        );
+        self.mint_threshold = threshold.map(|threshold| threshold.0);
+        self.mint_approvals_required = approvals_required;
+    }
+
+    pub(crate) fn assert_below_mint_threshold(&self, value: u128) {
+        if let Some(threshold) = self.mint_threshold {
+            require!(value <= threshold, "mint above threshold requires approval");
+        }
+    }
+
+    /// Opens a proposal counting the caller's approval. It executes as soon
+    /// as enough minters approved, which may be right away.
+    pub fn propose_mint(&mut self, to: AccountId, value: U128) -> u64 {
+        self.assert_not_migrating();
+        self.assert_minter();
+        let proposal_id = self.next_mint_proposal_id;
+        self.next_mint_proposal_id += 1;
+        self.mint_proposals.insert(
+            proposal_id,
+            MintProposal {
+                to,
+                value,
+                approvals: Vec::new(),
+            },
+        );
+        self.approve_mint(proposal_id);
+        proposal_id
+    }
+
+    pub fn approve_mint(&mut self, proposal_id: u64) {
+        self.assert_not_migrating();
+        self.assert_minter();
+        let minter_id = predecessor_account_id();
+        let proposal = self
+            .mint_proposals
+            .get_mut(&proposal_id)
+            .expect("no mint proposal");
+        require!(!proposal.approvals.contains(&minter_id), "already approved");
+        proposal.approvals.push(minter_id);
+        if proposal.approvals.len() >= self.mint_approvals_required as usize {
+            let MintProposal { to, value, .. } = self.mint_proposals.remove(&proposal_id).unwrap();
+            self.internal_mint(to, value);
+        }
+    }
+
+    pub fn mint_proposal(&self, proposal_id: u64) -> Option<&MintProposal> {
+        self.mint_proposals.get(&proposal_id)
+    }
+}
@@ -0,0 +1,47 @@
+use crate::ERC20;
+use near_sdk::{ext_contract, json_types::U128, AccountId, Gas};
+use std::fmt::Debug;
+
+const GAS_FOR_ON_ANY_TRANSFER: Gas = Gas(5_000_000_000_000);
+
+/// In-contract hook invoked after every balance movement, e.g. to keep a
+/// staking index in sync. Observers are not persisted, so the embedding
+/// contract registers them again on each call that needs them.
+pub trait TransferObserver: Debug {
+    fn on_transfer(&mut self, from: &AccountId, to: &AccountId, amount: u128);
+}
+
+#[ext_contract(ext_transfer_hook)]
+pub trait TransferHook {
+    fn on_any_transfer(&mut self, from: AccountId, to: AccountId, amount: U128);
+}
+
+impl ERC20 {
+    pub fn register_transfer_observer(&mut self, observer: Box<dyn TransferObserver>) {
+        self.observers.push(observer);
+    }
+
+    /// Contract that receives a fire-and-forget `on_any_transfer` call after
+    /// every balance movement. Its result is ignored.
+    pub fn set_global_hook(&mut self, global_hook: Option<AccountId>) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.global_hook = global_hook;
+    }
+
+    pub(crate) fn notify_transfer_observers(
+        &mut self,
+        from: &AccountId,
+        to: &AccountId,
+        amount: u128,
+    ) {
+        for observer in self.observers.iter_mut() {
+            observer.on_transfer(from, to, amount);
+        }
+        if let Some(global_hook) = &self.global_hook {
+            ext_transfer_hook::ext(global_hook.clone())
+                .with_static_gas(GAS_FOR_ON_ANY_TRANSFER)
+                .on_any_transfer(from.clone(), to.clone(), amount.into());
+        }
+    }
+}
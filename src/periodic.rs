@@ -0,0 +1,102 @@
+use crate::{nested_key, StorageKey, ERC20, NANOS_PER_SEC};
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env::{self, predecessor_account_id},
+    json_types::U128,
+    require,
+    serde::{Deserialize, Serialize},
+    store::UnorderedMap,
+    AccountId,
+};
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PeriodicAllowance {
+    pub amount_per_period: U128,
+    pub period_secs: u64,
+    /// Start of the current period in nanoseconds.
+    pub period_start: u64,
+    pub spent_this_period: U128,
+}
+
+impl ERC20 {
+    /// Lets `spender` pull up to `amount_per_period` through `transfer_from`
+    /// in every period of `period_secs`, starting now. While set it takes
+    /// the place of the plain allowance for this spender; a zero amount
+    /// removes it.
+    pub fn approve_periodic(
+        &mut self,
+        spender: AccountId,
+        amount_per_period: U128,
+        period_secs: u64,
+    ) {
+        self.assert_not_migrating();
+        let owner = predecessor_account_id();
+        if amount_per_period.0 == 0 {
+            if let Some(allowances) = self.periodic_allowances.get_mut(&owner) {
+                allowances.remove(&spender);
+                if allowances.is_empty() {
+                    self.periodic_allowances.remove(&owner);
+                }
+            }
+            return;
+        }
+        require!(period_secs > 0, "period must be positive");
+        self.assert_allowance_limits(&owner, &spender, amount_per_period.0);
+        if !self.periodic_allowances.contains_key(&owner) {
+            let prefix = [
+                nested_key(&self.storage_prefix, StorageKey::PeriodicAllowancesByOwner),
+                env::sha256(owner.as_bytes()),
+            ]
+            .concat();
+            self.periodic_allowances
+                .insert(owner.clone(), UnorderedMap::new(prefix));
+            self.internal_index_account(&owner);
+        }
+        self.periodic_allowances.get_mut(&owner).unwrap().insert(
+            spender,
+            PeriodicAllowance {
+                amount_per_period,
+                period_secs,
+                period_start: env::block_timestamp(),
+                spent_this_period: U128(0),
+            },
+        );
+    }
+
+    pub fn periodic_allowance(
+        &self,
+        owner: AccountId,
+        spender: AccountId,
+    ) -> Option<&PeriodicAllowance> {
+        self.periodic_allowances
+            .get(&owner)
+            .and_then(|allowances| allowances.get(&spender))
+    }
+
+    pub(crate) fn internal_spend_periodic_allowance(
+        &mut self,
+        owner: &AccountId,
+        spender: &AccountId,
+        value: u128,
+    ) {
+        let now = env::block_timestamp();
+        let allowance = self
+            .periodic_allowances
+            .get_mut(owner)
+            .and_then(|allowances| allowances.get_mut(spender))
+            .expect("no periodic allowance");
+        let period = allowance.period_secs * NANOS_PER_SEC;
+        let elapsed = now.saturating_sub(allowance.period_start);
+        if elapsed >= period {
+            allowance.period_start += elapsed - elapsed % period;
+            allowance.spent_this_period = U128(0);
+        }
+        let spent = allowance.spent_this_period.0.saturating_add(value);
+        require!(
+            spent <= allowance.amount_per_period.0,
+            "periodic allowance exhausted"
+        );
+        allowance.spent_this_period = spent.into();
+    }
+}
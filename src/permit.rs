@@ -0,0 +1,85 @@
+use crate::{signature::internal_verify_signature, ERC20};
+use near_sdk::{
+    borsh::BorshSerialize,
+    env::{self, predecessor_account_id},
+    json_types::U128,
+    require, AccountId,
+};
+
+pub const PERMIT_CHAIN_ID: &str = "near";
+
+impl ERC20 {
+    /// Registers the ed25519 key whose signatures `permit` accepts for the
+    /// caller.
+    pub fn set_permit_key(&mut self, public_key: Vec<u8>) {
+        self.assert_not_migrating();
+        self.permit_keys
+            .insert(predecessor_account_id(), public_key);
+    }
+
+    pub fn permit_nonce(&self, owner: AccountId) -> u64 {
+        self.permit_nonces.get(&owner).copied().unwrap_or(0)
+    }
+
+    /// Binds permits to this deployment so a signature cannot be replayed
+    /// against another contract or chain.
+    pub fn domain_separator(&self) -> Vec<u8> {
+        let domain = (PERMIT_CHAIN_ID, env::current_account_id(), &self.name);
+        env::sha256(&domain.try_to_vec().unwrap())
+    }
+
+    /// The bytes `owner` signs to approve `spender` for `value`.
+    pub fn permit_message(
+        &self,
+        owner: AccountId,
+        spender: AccountId,
+        value: U128,
+        nonce: u64,
+        deadline: u64,
+    ) -> Vec<u8> {
+        let permit = (owner, spender, value.0, nonce, deadline);
+        [self.domain_separator(), permit.try_to_vec().unwrap()].concat()
+    }
+
+    /// Approves `spender` on behalf of `owner` from a signature by the
+    /// owner's permit key. `deadline` is a block timestamp in nanoseconds.
+    pub fn permit(
+        &mut self,
+        owner: AccountId,
+        spender: AccountId,
+        value: U128,
+        deadline: u64,
+        signature: Vec<u8>,
+    ) {
+        self.assert_not_migrating();
+        require!(env::block_timestamp() <= deadline, "permit expired");
+        let public_key = self.permit_keys.get(&owner).expect("no permit key");
+        let nonce = self.permit_nonce(owner.clone());
+        let message = self.permit_message(owner.clone(), spender.clone(), value, nonce, deadline);
+        require!(
+            internal_verify_signature(&message, &signature, public_key),
+            "invalid signature"
+        );
+        self.permit_nonces.insert(owner.clone(), nonce + 1);
+        self.internal_approve(&owner, spender, value.0);
+    }
+
+    /// Applies a permit for the caller and spends it right away.
+    pub fn permit_transfer_from(
+        &mut self,
+        owner: AccountId,
+        to: AccountId,
+        value: U128,
+        deadline: u64,
+        signature: Vec<u8>,
+    ) -> bool {
+        self.permit(
+            owner.clone(),
+            predecessor_account_id(),
+            value,
+            deadline,
+            signature,
+        );
+        self.transfer_from(owner, to, value)
+    }
+}
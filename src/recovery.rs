@@ -0,0 +1,104 @@
+use crate::{events, ERC20, NANOS_PER_SEC};
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env::{self, predecessor_account_id},
+    json_types::U128,
+    require,
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+pub const RECOVERY_DELAY_SECS: u64 = 7 * 24 * 60 * 60;
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RecoveryRequest {
+    pub from: AccountId,
+    pub to: AccountId,
+    pub executable_at: u64,
+}
+
+impl ERC20 {
+    /// Starts moving the whole balance of `from`, whose keys are lost, to
+    /// `to`. It can be executed after `RECOVERY_DELAY_SECS`, giving `from`
+    /// the chance to veto with `cancel_recovery` if it is still in control.
+    pub fn request_recovery(&mut self, from: AccountId, to: AccountId) -> u64 {
+        self.assert_not_migrating();
+        self.assert_owner();
+        let request_id = self.next_recovery_id;
+        self.next_recovery_id += 1;
+        self.recovery_requests.insert(
+            request_id,
+            RecoveryRequest {
+                from,
+                to,
+                executable_at: env::block_timestamp() + RECOVERY_DELAY_SECS * NANOS_PER_SEC,
+            },
+        );
+        request_id
+    }
+
+    pub fn recovery_request(&self, request_id: u64) -> Option<&RecoveryRequest> {
+        self.recovery_requests.get(&request_id)
+    }
+
+    /// Moves the balance of `from` to `to`, logged as `ft_transfer`. Any lock
+    /// on it moves along, so locked tokens stay locked on the recovered
+    /// account.
+    pub fn execute_recovery(&mut self, request_id: u64) -> U128 {
+        self.assert_not_migrating();
+        self.assert_owner();
+        let request = self
+            .recovery_requests
+            .get(&request_id)
+            .expect("no recovery request");
+        require!(
+            env::block_timestamp() >= request.executable_at,
+            "recovery timelock active"
+        );
+        let RecoveryRequest { from, to, .. } = self.recovery_requests.remove(&request_id).unwrap();
+        let amount = *self.balance_of(from.clone()).unwrap_or(&0u128);
+        let locked = match self.balance.get_mut(&from) {
+            Some(entry) => std::mem::take(&mut entry.locked),
+            None => 0,
+        };
+        self.internal_move(&from, &to, amount);
+        if let Some(entry) = self.balance.get_mut(&to) {
+            entry.locked += locked;
+        }
+        amount.into()
+    }
+
+    pub fn cancel_recovery(&mut self, request_id: u64) {
+        self.assert_not_migrating();
+        let request = self
+            .recovery_requests
+            .get(&request_id)
+            .expect("no recovery request");
+        require!(
+            predecessor_account_id() == request.from,
+            "only recovered account"
+        );
+        self.recovery_requests.remove(&request_id);
+    }
+
+    /// Moves tokens parked on the token contract's own account to `to` and
+    /// returns the amount moved.
+    pub fn recover_self_balance(&mut self, to: AccountId) -> U128 {
+        self.assert_not_migrating();
+        self.assert_owner();
+        let contract_id = env::current_account_id();
+        let amount = *self.balance_of(contract_id.clone()).unwrap_or(&0u128);
+        require!(amount > 0, "no self balance");
+        self.internal_set_balance(&contract_id, 0);
+        self.internal_credit(&to, amount);
+        events::FtTransfer {
+            old_owner_id: &contract_id,
+            new_owner_id: &to,
+            amount: amount.into(),
+            correlation_id: None,
+        }
+        .emit(self);
+        amount.into()
+    }
+}
@@ -0,0 +1,243 @@
+#[cfg(feature = "lookup-balances")]
+use crate::{nested_key, StorageKey};
+use crate::{uint_types::U256, ERC20};
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::U128,
+    require,
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Redenomination {
+    pub id: u32,
+    pub old_decimals: u8,
+    pub new_decimals: u8,
+    /// Next position in `account_index`.
+    pub account_cursor: u64,
+    pub pending_mint_cursor: u64,
+    pub mint_record_cursor: u64,
+    pub mint_proposal_cursor: u64,
+    /// Accounts with a non-zero balance rescaled so far.
+    pub rescaled_holders: u64,
+    /// `holder_count` when the redenomination started.
+    pub holders: u64,
+    /// `total_supply` before rescaling.
+    pub old_total_supply: U128,
+    /// Balances rescaled so far, before and after rescaling. Each balance
+    /// truncates on its own, so `total_supply` is recomputed from these
+    /// once every account is done.
+    pub old_held: U128,
+    pub new_held: U128,
+}
+
+impl Redenomination {
+    fn rescale(&self, amount: u128) -> u128 {
+        let amount = U256::from(amount);
+        let scaled = if self.new_decimals >= self.old_decimals {
+            amount * U256::exp10((self.new_decimals - self.old_decimals).into())
+        } else {
+            amount / U256::exp10((self.old_decimals - self.new_decimals).into())
+        };
+        require!(
+            scaled <= U256::from(u128::MAX),
+            "redenominated amount overflow"
+        );
+        scaled.as_u128()
+    }
+}
+
+impl ERC20 {
+    /// Starts rescaling every token amount to `new_decimals`, truncating
+    /// when scaling down. Supply figures and configured amounts are
+    /// rescaled here; per-account state, pending mints, mint records and
+    /// mint proposals follow in `redenominate_batch` calls. Every mutating
+    /// method is blocked until the last batch switches `decimals` and
+    /// recomputes `total_supply` from the truncated balances. The
+    /// history logs keep the amounts they recorded. Wrapped NEAR is redeemed
+    /// 1:1, so nothing may be wrapped meanwhile.
+    pub fn redenominate(&mut self, new_decimals: u8) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        require!(self.wrapped_supply == 0, "wrapped tokens outstanding");
+        let redenomination = Redenomination {
+            id: self.next_redenomination_id,
+            old_decimals: self.decimals,
+            new_decimals,
+            account_cursor: 0,
+            pending_mint_cursor: 0,
+            mint_record_cursor: 0,
+            mint_proposal_cursor: 0,
+            rescaled_holders: 0,
+            holders: self.holder_count,
+            old_total_supply: self.total_supply.into(),
+            old_held: 0.into(),
+            new_held: 0.into(),
+        };
+        self.next_redenomination_id += 1;
+
+        let rescale = |amount: u128| redenomination.rescale(amount);
+        self.total_supply = rescale(self.total_supply);
+        self.max_supply = self.max_supply.map(rescale);
+        self.total_burned = rescale(self.total_burned);
+        self.min_supply = rescale(self.min_supply);
+        self.max_allowance = self.max_allowance.map(rescale);
+        self.min_balance = rescale(self.min_balance);
+        self.min_fee = rescale(self.min_fee);
+        self.max_fee = self.max_fee.map(rescale);
+        self.faucet_amount = rescale(self.faucet_amount);
+        self.referral_bonus = rescale(self.referral_bonus);
+        self.referral_pool = rescale(self.referral_pool);
+        self.mint_threshold = self.mint_threshold.map(rescale);
+        if let Some(emission) = self.emission.as_mut() {
+            emission.rate_per_second = rescale(emission.rate_per_second);
+        }
+
+        self.migrating = true;
+        self.redenomination = Some(redenomination);
+    }
+
+    pub fn redenomination(&self) -> Option<&Redenomination> {
+        self.redenomination.as_ref()
+    }
+
+    /// Rescales up to `limit` more accounts, pending mints, mint records
+    /// and mint proposals. Returns true once everything is rescaled, which
+    /// completes the redenomination. With `lookup-balances` accounts cannot
+    /// be enumerated and go through `redenominate_accounts` instead.
+    pub fn redenominate_batch(&mut self, limit: u64) -> bool {
+        self.assert_owner();
+        let mut redenomination = self.redenomination.clone().expect("no redenomination");
+        let mut budget = limit;
+
+        #[cfg(not(feature = "lookup-balances"))]
+        while budget > 0 && redenomination.account_cursor < self.account_index.len() as u64 {
+            let account_id = self.account_index[redenomination.account_cursor as u32].clone();
+            if self.internal_redenominate_account(&mut redenomination, &account_id) {
+                redenomination.rescaled_holders += 1;
+            }
+            redenomination.account_cursor += 1;
+            budget -= 1;
+        }
+
+        while budget > 0 && redenomination.pending_mint_cursor < self.next_pending_mint_id {
+            if let Some(pending_mint) = self
+                .pending_mints
+                .get_mut(&redenomination.pending_mint_cursor)
+            {
+                pending_mint.value = redenomination.rescale(pending_mint.value.0).into();
+            }
+            redenomination.pending_mint_cursor += 1;
+            budget -= 1;
+        }
+        while budget > 0 && redenomination.mint_record_cursor < self.next_mint_record_id {
+            if let Some(record) = self
+                .mint_records
+                .get_mut(&redenomination.mint_record_cursor)
+            {
+                record.amount = redenomination.rescale(record.amount.0).into();
+            }
+            redenomination.mint_record_cursor += 1;
+            budget -= 1;
+        }
+        while budget > 0 && redenomination.mint_proposal_cursor < self.next_mint_proposal_id {
+            if let Some(proposal) = self
+                .mint_proposals
+                .get_mut(&redenomination.mint_proposal_cursor)
+            {
+                proposal.value = redenomination.rescale(proposal.value.0).into();
+            }
+            redenomination.mint_proposal_cursor += 1;
+            budget -= 1;
+        }
+
+        self.internal_finish_redenomination(redenomination)
+    }
+
+    #[cfg(feature = "lookup-balances")]
+    /// Rescales the given accounts; accounts already rescaled are skipped.
+    /// The redenomination cannot complete before every account holding a
+    /// balance was passed. Accounts that only granted allowances or hold
+    /// reservations are not counted, so the caller must include them too.
+    /// Returns true once everything is rescaled.
+    pub fn redenominate_accounts(&mut self, account_ids: Vec<AccountId>) -> bool {
+        self.assert_owner();
+        self.assert_batch_size(account_ids.len());
+        let mut redenomination = self.redenomination.clone().expect("no redenomination");
+        let mut rescaled = near_sdk::store::LookupSet::new(
+            [
+                nested_key(&self.storage_prefix, StorageKey::RedenominatedAccounts),
+                redenomination.id.to_le_bytes().to_vec(),
+            ]
+            .concat(),
+        );
+        for account_id in &account_ids {
+            if rescaled.insert(account_id.clone())
+                && self.internal_redenominate_account(&mut redenomination, account_id)
+            {
+                redenomination.rescaled_holders += 1;
+            }
+        }
+        self.internal_finish_redenomination(redenomination)
+    }
+
+    /// Returns whether `account_id` held a balance.
+    fn internal_redenominate_account(
+        &mut self,
+        redenomination: &mut Redenomination,
+        account_id: &AccountId,
+    ) -> bool {
+        let mut holder = false;
+        if let Some(entry) = self.balance.get(account_id).cloned() {
+            holder = entry.amount > 0;
+            let amount = redenomination.rescale(entry.amount);
+            redenomination.old_held = (redenomination.old_held.0 + entry.amount).into();
+            redenomination.new_held = (redenomination.new_held.0 + amount).into();
+            self.internal_set_balance(account_id, amount);
+            self.balance.get_mut(account_id).unwrap().locked = redenomination.rescale(entry.locked);
+        }
+        if let Some(allowed) = self.allowed.get_mut(account_id) {
+            for allowance in allowed.values_mut() {
+                *allowance = redenomination.rescale(*allowance);
+            }
+        }
+        if let Some(allowances) = self.periodic_allowances.get_mut(account_id) {
+            for allowance in allowances.values_mut() {
+                allowance.amount_per_period =
+                    redenomination.rescale(allowance.amount_per_period.0).into();
+                allowance.spent_this_period =
+                    redenomination.rescale(allowance.spent_this_period.0).into();
+            }
+        }
+        if let Some(reservation) = self.reservations.get_mut(account_id) {
+            reservation.amount = redenomination.rescale(reservation.amount);
+        }
+        holder
+    }
+
+    fn internal_finish_redenomination(&mut self, redenomination: Redenomination) -> bool {
+        #[cfg(not(feature = "lookup-balances"))]
+        let accounts_done = redenomination.account_cursor == self.account_index.len() as u64;
+        #[cfg(feature = "lookup-balances")]
+        let accounts_done = redenomination.rescaled_holders == redenomination.holders;
+        let done = accounts_done
+            && redenomination.pending_mint_cursor == self.next_pending_mint_id
+            && redenomination.mint_record_cursor == self.next_mint_record_id
+            && redenomination.mint_proposal_cursor == self.next_mint_proposal_id;
+        if done {
+            let unheld = redenomination
+                .old_total_supply
+                .0
+                .saturating_sub(redenomination.old_held.0);
+            self.total_supply = redenomination.new_held.0 + redenomination.rescale(unheld);
+            self.decimals = redenomination.new_decimals;
+            self.redenomination = None;
+            self.migrating = false;
+        } else {
+            self.redenomination = Some(redenomination);
+        }
+        done
+    }
+}
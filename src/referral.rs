@@ -0,0 +1,85 @@
+use crate::{events, ERC20};
+use near_sdk::{
+    env::{self, predecessor_account_id},
+    json_types::U128,
+    require, AccountId,
+};
+
+impl ERC20 {
+    /// Pays `referral_bonus` out of the referral pool to the referrer named
+    /// in an `ft_transfer`, while the pool can cover it.
+    pub fn configure_referral(&mut self, enabled: bool, bonus: U128) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.referral_enabled = enabled;
+        self.referral_bonus = bonus.0;
+    }
+
+    /// Moves `amount` of the owner's tokens into the referral pool. Pool
+    /// movements are logged with the token contract as the pool's owner.
+    pub fn fund_referral_pool(&mut self, amount: U128) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        let owner_id = predecessor_account_id();
+        let balance = *self.balance_of(owner_id.clone()).unwrap_or(&0u128);
+        require!(balance >= amount.0, "insufficient balance");
+        require!(
+            balance - amount.0 >= self.locked_of(owner_id.clone()).0,
+            "balance is locked"
+        );
+        self.internal_set_balance(&owner_id, balance - amount.0);
+        self.referral_pool += amount.0;
+        events::FtTransfer {
+            old_owner_id: &owner_id,
+            new_owner_id: &env::current_account_id(),
+            amount,
+            correlation_id: None,
+        }
+        .emit(self);
+    }
+
+    /// Moves `amount` out of the referral pool back to the owner.
+    pub fn withdraw_referral_pool(&mut self, amount: U128) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        require!(
+            amount.0 <= self.referral_pool,
+            "amount exceeds referral pool"
+        );
+        let owner_id = predecessor_account_id();
+        self.referral_pool -= amount.0;
+        self.internal_credit(&owner_id, amount.0);
+        events::FtTransfer {
+            old_owner_id: &env::current_account_id(),
+            new_owner_id: &owner_id,
+            amount,
+            correlation_id: None,
+        }
+        .emit(self);
+    }
+
+    pub fn referral_pool(&self) -> U128 {
+        self.referral_pool.into()
+    }
+
+    pub(crate) fn internal_credit_referrer(&mut self, sender_id: &AccountId, referrer: &AccountId) {
+        if !self.referral_enabled
+            || referrer == sender_id
+            || self.referral_bonus == 0
+            || self.referral_pool < self.referral_bonus
+            || self.is_burn_address(referrer)
+            || self.check_receive(referrer).is_err()
+        {
+            return;
+        }
+        self.referral_pool -= self.referral_bonus;
+        self.internal_credit(referrer, self.referral_bonus);
+        events::FtTransfer {
+            old_owner_id: &env::current_account_id(),
+            new_owner_id: referrer,
+            amount: self.referral_bonus.into(),
+            correlation_id: None,
+        }
+        .emit(self);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::ERC20;
+use near_sdk::{
+    env::predecessor_account_id,
+    require,
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoleFlags {
+    pub owner: bool,
+    pub minter: bool,
+    pub pauser: bool,
+}
+
+impl ERC20 {
+    pub fn add_minter(&mut self, account_id: AccountId) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.minters.insert(account_id);
+    }
+
+    pub fn remove_minter(&mut self, account_id: AccountId) {
+        self.assert_not_migrating();
+        self.assert_owner();
+        self.minters.remove(&account_id);
+    }
+
+    /// The owner can always mint; `minters` lists everyone else who can.
+    pub fn is_minter(&self, account_id: AccountId) -> bool {
+        account_id == self.owner_id || self.minters.contains(&account_id)
+    }
+
+    pub fn minters_count(&self) -> u64 {
+        self.minters.len().into()
+    }
+
+    pub fn roles(&self, account_id: AccountId) -> RoleFlags {
+        RoleFlags {
+            owner: account_id == self.owner_id,
+            pauser: account_id == self.pauser_id,
+            minter: self.is_minter(account_id),
+        }
+    }
+
+    pub(crate) fn assert_minter(&self) {
+        require!(self.is_minter(predecessor_account_id()), "only minter");
+    }
+}
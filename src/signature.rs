@@ -0,0 +1,22 @@
+use ed25519_dalek::{PublicKey, Signature, Verifier};
+
+/// Checks an ed25519 `signature` of `message` by `public_key`; malformed
+/// keys or signatures simply fail verification.
+pub(crate) fn internal_verify_signature(
+    message: &[u8],
+    signature: &[u8],
+    public_key: &[u8],
+) -> bool {
+    let (Ok(signature), Ok(public_key)) = (
+        Signature::from_bytes(signature),
+        PublicKey::from_bytes(public_key),
+    ) else {
+        return false;
+    };
+    public_key.verify(message, &signature).is_ok()
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+pub fn verify_signature(message: Vec<u8>, signature: Vec<u8>, public_key: Vec<u8>) -> bool {
+    internal_verify_signature(&message, &signature, &public_key)
+}
@@ -0,0 +1,266 @@
+use crate::ERC20;
+use near_sdk::{
+    borsh::BorshSerialize,
+    env::{self, predecessor_account_id},
+    json_types::U128,
+    require,
+    serde::{Deserialize, Serialize},
+    AccountId, Balance, Promise,
+};
+
+/// NEP-145 storage management.
+pub const STORAGE_MANAGEMENT_STANDARD: &str = "nep145";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalanceBounds {
+    pub min: U128,
+    pub max: Option<U128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalance {
+    pub total: U128,
+    pub available: U128,
+}
+
+impl ERC20 {
+    /// Inserts and removes a longest-possible account id to learn how many
+    /// bytes a balance entry, together with its `account_index` slot,
+    /// occupies.
+    pub(crate) fn measure_account_storage_usage(&mut self) {
+        let initial_storage_usage = env::storage_usage();
+        let tmp_account_id: AccountId = "a".repeat(64).parse().unwrap();
+        self.internal_set_balance(&tmp_account_id, 0);
+        self.flush_balances();
+        self.account_storage_usage = env::storage_usage() - initial_storage_usage;
+        self.balance.remove(&tmp_account_id);
+        #[cfg(not(feature = "lookup-balances"))]
+        {
+            self.account_index.pop();
+            self.indexed_accounts.remove(&tmp_account_id);
+        }
+        self.flush_balances();
+    }
+
+    pub(crate) fn flush_balances(&mut self) {
+        self.balance.flush();
+        #[cfg(not(feature = "lookup-balances"))]
+        self.account_index.flush();
+    }
+
+    /// Every account occupies the same storage, so the minimum and maximum
+    /// deposit are both the cost of one balance entry.
+    pub fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        let account_cost = U128(self.account_storage_cost());
+        StorageBalanceBounds {
+            min: account_cost,
+            max: Some(account_cost),
+        }
+    }
+
+    fn account_storage_cost(&self) -> Balance {
+        self.account_storage_usage as Balance * env::storage_byte_cost()
+    }
+
+    fn internal_storage_balance(&self, deposit: Balance) -> StorageBalance {
+        StorageBalance {
+            total: deposit.into(),
+            available: deposit.saturating_sub(self.account_storage_cost()).into(),
+        }
+    }
+
+    /// Registers `account_id` (or the caller) and keeps the attached deposit
+    /// up to the maximum bound, refunding the rest. With `registration_only`
+    /// anything above the minimum is refunded. Must be exposed as
+    /// `#[payable]`.
+    pub fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        self.assert_not_migrating();
+        let account_id = account_id.unwrap_or_else(predecessor_account_id);
+        let (balance, refund) = self.internal_storage_deposit(
+            account_id,
+            env::attached_deposit(),
+            registration_only.unwrap_or(false),
+        );
+        if refund > 0 {
+            Promise::new(predecessor_account_id()).transfer(refund);
+        }
+        balance
+    }
+
+    /// Registers every account with an equal share of the attached deposit
+    /// and refunds the indivisible remainder. Must be exposed as
+    /// `#[payable]`.
+    pub fn storage_deposit_batch(&mut self, accounts: Vec<AccountId>) -> Vec<StorageBalance> {
+        self.assert_not_migrating();
+        require!(!accounts.is_empty(), "no accounts");
+        self.assert_batch_size(accounts.len());
+        let deposit = env::attached_deposit();
+        let share = deposit / accounts.len() as Balance;
+        let mut refund = deposit % accounts.len() as Balance;
+        let balances = accounts
+            .into_iter()
+            .map(|account_id| {
+                let (balance, account_refund) =
+                    self.internal_storage_deposit(account_id, share, false);
+                refund += account_refund;
+                balance
+            })
+            .collect();
+        if refund > 0 {
+            Promise::new(predecessor_account_id()).transfer(refund);
+        }
+        balances
+    }
+
+    /// Returns the new storage balance and the part of `amount` to refund.
+    fn internal_storage_deposit(
+        &mut self,
+        account_id: AccountId,
+        amount: Balance,
+        registration_only: bool,
+    ) -> (StorageBalance, Balance) {
+        let bounds = self.storage_balance_bounds();
+        let min = bounds.min.0;
+        let max = bounds.max.map_or(Balance::MAX, |max| max.0);
+
+        let previous = self.storage_deposits.get(&account_id).copied();
+        let (deposit, refund) = match previous {
+            Some(deposit) if registration_only => (deposit, amount),
+            Some(deposit) => {
+                let kept = max.saturating_sub(deposit).min(amount);
+                (deposit + kept, amount - kept)
+            }
+            None => {
+                require!(amount >= min, "insufficient deposit for storage");
+                if !self.balance.contains_key(&account_id) {
+                    self.internal_set_balance(&account_id, 0);
+                }
+                let kept = if registration_only {
+                    min
+                } else {
+                    amount.min(max)
+                };
+                (kept, amount - kept)
+            }
+        };
+        self.total_storage_deposits += deposit - previous.unwrap_or(0);
+        self.storage_deposits.insert(account_id, deposit);
+        (self.internal_storage_balance(deposit), refund)
+    }
+
+    /// Refunds the part of the caller's deposit that does not back their
+    /// balance entry. Must be exposed as `#[payable]`.
+    pub fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        self.assert_not_migrating();
+        require!(
+            env::attached_deposit() == 1,
+            "requires attached deposit of exactly 1 yoctoNEAR"
+        );
+        let account_id = predecessor_account_id();
+        let deposit = *self
+            .storage_deposits
+            .get(&account_id)
+            .expect("account not registered");
+        let available = self.internal_storage_balance(deposit).available.0;
+        let amount = amount.map(|amount| amount.0).unwrap_or(available);
+        require!(
+            amount <= available,
+            "amount exceeds available storage balance"
+        );
+
+        let deposit = deposit - amount;
+        self.total_storage_deposits -= amount;
+        self.storage_deposits.insert(account_id.clone(), deposit);
+        if amount > 0 {
+            Promise::new(account_id).transfer(amount);
+        }
+        self.internal_storage_balance(deposit)
+    }
+
+    /// Removes the caller's registration and refunds their storage deposit.
+    /// A non-zero balance is burned with `force`, and rejected otherwise.
+    /// Returns false when the caller is not registered. Must be exposed as
+    /// `#[payable]`.
+    pub fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        self.assert_not_migrating();
+        require!(
+            env::attached_deposit() == 1,
+            "requires attached deposit of exactly 1 yoctoNEAR"
+        );
+        let account_id = predecessor_account_id();
+        if !self.storage_deposits.contains_key(&account_id) {
+            return false;
+        }
+        let balance = *self.balance_of(account_id.clone()).unwrap_or(&0);
+        if balance > 0 {
+            require!(
+                force.unwrap_or(false),
+                "cannot unregister with a positive balance"
+            );
+            require!(
+                self.locked_of(account_id.clone()).0 == 0,
+                "balance is locked"
+            );
+            self.assert_burn_above_min_supply(balance);
+        }
+        if balance > 0 {
+            self.internal_burn(&account_id, balance);
+        }
+        self.internal_remove_balance(&account_id);
+        true
+    }
+
+    /// `None` unless `account_id` registered through `storage_deposit`.
+    pub fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage_deposits
+            .get(&account_id)
+            .map(|deposit| self.internal_storage_balance(*deposit))
+    }
+
+    pub fn storage_usage(&self) -> u64 {
+        env::storage_usage()
+    }
+
+    /// Borsh size of the account id plus its balance entry, excluding the
+    /// collection's own key prefix and index overhead; 0 if unregistered.
+    pub fn account_entry_size(&self, account_id: AccountId) -> u64 {
+        let Some(entry) = self.balance.get(&account_id) else {
+            return 0;
+        };
+        (account_id.try_to_vec().unwrap().len() + entry.try_to_vec().unwrap().len()) as u64
+    }
+
+    /// NEAR the contract must keep to pay for the storage it occupies.
+    pub fn storage_staking_reserve(&self) -> U128 {
+        (Balance::from(env::storage_usage()) * env::storage_byte_cost()).into()
+    }
+
+    /// NEAR the contract owes back: the backing of wrapped tokens, storage
+    /// deposits and reservation deposits.
+    pub fn near_liabilities(&self) -> U128 {
+        (self.wrapped_supply + self.total_storage_deposits + self.total_reservation_deposits).into()
+    }
+
+    /// Sweeps native NEAR from the contract, never dipping into
+    /// `storage_staking_reserve` or the NEAR owed in `near_liabilities`.
+    pub fn withdraw_near(&mut self, to: AccountId, amount: U128) -> Promise {
+        self.assert_not_migrating();
+        self.assert_owner();
+        let reserve = self.storage_staking_reserve().0;
+        require!(
+            env::account_balance().saturating_sub(amount.0) >= reserve,
+            "withdrawal would breach storage reserve"
+        );
+        require!(
+            env::account_balance() - amount.0 - reserve >= self.near_liabilities().0,
+            "withdrawal would breach liabilities"
+        );
+        Promise::new(to).transfer(amount.0)
+    }
+}
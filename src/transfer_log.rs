@@ -0,0 +1,51 @@
+use crate::ERC20;
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::U128,
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+pub const TRANSFER_LOG_CAPACITY: u64 = 100;
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TransferRecord {
+    pub id: u64,
+    pub from: AccountId,
+    pub to: AccountId,
+    pub amount: U128,
+    pub timestamp: u64,
+}
+
+impl ERC20 {
+    /// Appends to the ring buffer, overwriting the oldest record once
+    /// `TRANSFER_LOG_CAPACITY` is reached.
+    pub(crate) fn record_transfer(&mut self, from: &AccountId, to: &AccountId, amount: u128) {
+        let record = TransferRecord {
+            id: self.transfer_count,
+            from: from.clone(),
+            to: to.clone(),
+            amount: amount.into(),
+            timestamp: env::block_timestamp(),
+        };
+        if self.transfer_count < TRANSFER_LOG_CAPACITY {
+            self.transfer_log.push(record);
+        } else {
+            let slot = (self.transfer_count % TRANSFER_LOG_CAPACITY) as u32;
+            self.transfer_log.set(slot, record);
+        }
+        self.transfer_count += 1;
+    }
+
+    /// Returns the retained records with `id >= cursor` and the cursor to
+    /// pass next time. Records older than the ring buffer are skipped.
+    pub fn events_since(&self, cursor: u64) -> (Vec<TransferRecord>, u64) {
+        let oldest = self.transfer_count.saturating_sub(TRANSFER_LOG_CAPACITY);
+        let records = (cursor.max(oldest)..self.transfer_count)
+            .map(|id| self.transfer_log[(id % TRANSFER_LOG_CAPACITY) as u32].clone())
+            .collect();
+        (records, self.transfer_count)
+    }
+}
@@ -0,0 +1,109 @@
+use crate::ERC20;
+use near_sdk::{
+    env::{self, predecessor_account_id},
+    ext_contract,
+    json_types::U128,
+    require, AccountId, Gas, Promise, PromiseResult,
+};
+
+const GAS_FOR_RESOLVE_WITHDRAW: Gas = Gas(5_000_000_000_000);
+
+#[ext_contract(ext_withdraw_resolver)]
+pub trait WithdrawResolver {
+    fn resolve_withdraw(&mut self, account_id: AccountId, amount: U128) -> bool;
+}
+
+impl ERC20 {
+    /// Wraps the attached NEAR one-to-one into tokens for the caller, subject
+    /// to the same supply cap and pause as any mint. Must be exposed as
+    /// `#[payable]`.
+    pub fn near_deposit(&mut self) {
+        self.assert_not_migrating();
+        let amount = env::attached_deposit();
+        require!(amount > 0, "requires attached deposit");
+        let account_id = predecessor_account_id();
+        self.internal_mint(account_id.clone(), amount.into());
+        self.internal_add_wrapped(&account_id, amount);
+    }
+
+    /// NEAR held on behalf of wrapped tokens; only this much can be
+    /// redeemed through `near_withdraw`.
+    pub fn wrapped_supply(&self) -> U128 {
+        self.wrapped_supply.into()
+    }
+
+    /// How much of the account's balance it may redeem for NEAR.
+    pub fn wrapped_balance_of(&self, account_id: AccountId) -> U128 {
+        self.wrapped_balances
+            .get(&account_id)
+            .copied()
+            .unwrap_or(0)
+            .into()
+    }
+
+    fn internal_add_wrapped(&mut self, account_id: &AccountId, amount: u128) {
+        let wrapped = self.wrapped_balances.get(account_id).copied().unwrap_or(0);
+        self.wrapped_balances
+            .insert(account_id.clone(), wrapped + amount);
+        self.wrapped_supply += amount;
+    }
+
+    /// Wrapped NEAR is only redeemable against tokens still held, so
+    /// debiting an account below its wrapped balance releases the
+    /// difference from `wrapped_supply`.
+    pub(crate) fn internal_clamp_wrapped(&mut self, account_id: &AccountId, balance: u128) {
+        let Some(wrapped) = self.wrapped_balances.get(account_id).copied() else {
+            return;
+        };
+        if wrapped <= balance {
+            return;
+        }
+        self.wrapped_supply -= wrapped - balance;
+        if balance == 0 {
+            self.wrapped_balances.remove(account_id);
+        } else {
+            self.wrapped_balances.insert(account_id.clone(), balance);
+        }
+    }
+
+    /// Burns `amount` before sending the NEAR back so a failed transfer can
+    /// be undone by `resolve_withdraw`. Only NEAR the caller wrapped can be
+    /// redeemed. Must be exposed as `#[payable]`.
+    pub fn near_withdraw(&mut self, amount: U128) -> Promise {
+        self.assert_not_migrating();
+        require!(
+            env::attached_deposit() == 1,
+            "requires attached deposit of exactly 1 yoctoNEAR"
+        );
+        let account_id = predecessor_account_id();
+        let wrapped = self.wrapped_balance_of(account_id.clone()).0;
+        require!(amount.0 <= wrapped, "amount exceeds wrapped balance");
+        self.assert_burnable(&account_id, amount.0);
+        if wrapped == amount.0 {
+            self.wrapped_balances.remove(&account_id);
+        } else {
+            self.wrapped_balances
+                .insert(account_id.clone(), wrapped - amount.0);
+        }
+        self.wrapped_supply -= amount.0;
+        self.internal_burn(&account_id, amount.0);
+        Promise::new(account_id.clone()).transfer(amount.0).then(
+            ext_withdraw_resolver::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_WITHDRAW)
+                .resolve_withdraw(account_id, amount),
+        )
+    }
+
+    /// Re-credits the burned tokens if the NEAR transfer failed and returns
+    /// whether the withdraw went through. Must be exposed as `#[private]`.
+    pub fn resolve_withdraw(&mut self, account_id: AccountId, amount: U128) -> bool {
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            return true;
+        }
+        self.internal_credit(&account_id, amount.0);
+        self.internal_add_wrapped(&account_id, amount.0);
+        self.total_supply += amount.0;
+        self.total_burned -= amount.0;
+        false
+    }
+}